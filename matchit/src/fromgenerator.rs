@@ -64,6 +64,16 @@ pub trait FromAdaptFnCall: Iterator
         split.finalize()
     }
 
+    fn reduce_with<Red>(self, mut reducer: Red) -> Red::Reduced
+    where
+        Red: ReducerTrait<Input = Self::Item>
+    {
+        for i in self {
+            reducer.next(i);
+        }
+        reducer.finalize()
+    }
+
 }
 
 impl<T> FromAdaptFnCall for T where T: Iterator {}
@@ -79,4 +89,12 @@ mod tests {
         assert_eq!(4, data.into_iter().groupby(|a: &u32, b: &u32| b >= a).count());
     }
 
+    #[test]
+    fn reduce_with() {
+        use crate::generator::approx::{DistinctCount, TopK};
+        let data = [0,1,2,0,0,2,3,0,4,3];
+        assert_eq!(5, data.into_iter().reduce_with(DistinctCount::new(10)).estimate());
+        assert_eq!((0, 4, 0), data.into_iter().reduce_with(TopK::new(3)).top()[0]);
+    }
+
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use std::{marker::PhantomData, ops::{Add, ControlFlow}};
 
-use super::{groupby::{GroupBy, Merge}, reducer::*};
+use super::{approx::{DistinctCount, Quantiles, TopK}, groupby::{GroupBy, Merge}, reducer::*, sequence::Sequence, window::Window};
 
 
 /// An adapter is called either by a reducer or another adapter.
@@ -97,6 +97,67 @@ where
         Adapter::new( Merge::new(f, keyfn), self )
     }
 
+    #[inline(always)]
+    fn window_tumbling<KeyFn,RedFn,Red,R>(self, keyfn: KeyFn, width: i64, reducerfn: RedFn) -> Adapter<Window<Self::Output,KeyFn,RedFn,Red,R>, Self>
+    where
+        KeyFn: Fn(&Self::Output) -> i64,
+        RedFn: Fn(&i64) -> Red,
+        Red: ReducerTrait<Input = Self::Output, Reduced = R>,
+        Self::Output: Clone
+    {
+        Adapter::new( Window::new(keyfn, width, width, reducerfn), self )
+    }
+
+    #[inline(always)]
+    fn window_sliding<KeyFn,RedFn,Red,R>(self, keyfn: KeyFn, width: i64, step: i64, reducerfn: RedFn) -> Adapter<Window<Self::Output,KeyFn,RedFn,Red,R>, Self>
+    where
+        KeyFn: Fn(&Self::Output) -> i64,
+        RedFn: Fn(&i64) -> Red,
+        Red: ReducerTrait<Input = Self::Output, Reduced = R>,
+        Self::Output: Clone
+    {
+        Adapter::new( Window::new(keyfn, width, step, reducerfn), self )
+    }
+
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    fn sequence<P1,P2,P3,KeyFn,TimeFn,Key>(self, start: P1, end: P2, cancel: P3, keyfn: KeyFn, timefn: TimeFn, within: i64) -> Adapter<Sequence<Self::Output,P1,P2,P3,KeyFn,TimeFn,Key>, Self>
+    where
+        P1: Fn(&Self::Output) -> bool,
+        P2: Fn(&Self::Output) -> bool,
+        P3: Fn(&Self::Output) -> bool,
+        KeyFn: Fn(&Self::Output) -> Key,
+        TimeFn: Fn(&Self::Output) -> i64,
+        Self::Output: Clone,
+        Key: Ord
+    {
+        Adapter::new( Sequence::new(start, end, cancel, keyfn, timefn, within), self )
+    }
+
+    #[inline(always)]
+    fn quantiles(self, k: usize) -> Reducer<Quantiles, Self>
+    where
+        Self: AdapterTrait<Output = f64>
+    {
+        Reducer::new ( Quantiles::new(k), self )
+    }
+
+    #[inline(always)]
+    fn distinct_count(self, precision: u32) -> Reducer<DistinctCount<Self::Output>, Self>
+    where
+        Self::Output: std::hash::Hash
+    {
+        Reducer::new ( DistinctCount::new(precision), self )
+    }
+
+    #[inline(always)]
+    fn top_k(self, capacity: usize) -> Reducer<TopK<Self::Output>, Self>
+    where
+        Self::Output: Ord + Clone
+    {
+        Reducer::new ( TopK::new(capacity), self )
+    }
+
 }
 
 pub struct NilAdapter<I>(PhantomData<I>);
@@ -0,0 +1,298 @@
+use std::{collections::{hash_map::DefaultHasher, BTreeMap}, hash::{Hash, Hasher}, marker::PhantomData};
+
+use super::reducer::ReducerTrait;
+
+///
+/// Quantiles
+///
+/// KLL sketch of ``f64`` values, e.g., message sizes or inter-arrival times. The memory is bounded by about ``3 * k``
+/// values, the rank error is about ``1.7 / k`` (``k = 200``: below 1%). NaN values are ignored.
+///
+/// The reduced value is the sketch itself; sketches of partitions are combined with ``merge``.
+///
+#[derive(Clone, Debug)]
+pub struct Quantiles {
+    k: usize,
+    levels: Vec<Vec<f64>>,
+    count: u64,
+    // alternates the compacted half instead of a random choice, which keeps the results reproducible
+    odd: bool,
+}
+
+impl Quantiles {
+    pub fn new(k: usize) -> Self {
+        assert!(k >= 8, "k needs to be at least 8");
+        Self { k, levels: vec![vec![]], count: 0, odd: false }
+    }
+
+    /// Number of values.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Approximate ``q``-quantile for ``q`` in ``[0, 1]``, ``None`` if the sketch is empty.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let items = self.items();
+        let target = (q.clamp(0.0, 1.0) * self.count as f64).ceil().max(1.0) as u64;
+        let mut rank = 0;
+        for (value, weight) in &items {
+            rank += weight;
+            if rank >= target {
+                return Some(*value)
+            }
+        }
+        items.last().map(|(value, _)| *value)
+    }
+
+    /// Approximate fraction of the values less than or equal to ``value``.
+    pub fn rank(&self, value: f64) -> f64 {
+        if self.count == 0 {
+            return 0.0
+        }
+        let below: u64 = self.items().iter().take_while(|(v, _)| *v <= value).map(|(_, weight)| weight).sum();
+        below as f64 / self.count as f64
+    }
+
+    /// Adds the values of ``other``.
+    pub fn merge(&mut self, other: Quantiles) {
+        for (level, values) in other.levels.into_iter().enumerate() {
+            if level == self.levels.len() {
+                self.levels.push(vec![]);
+            }
+            self.levels[level].extend(values);
+        }
+        self.count += other.count;
+        self.compress();
+    }
+
+    fn items(&self) -> Vec<(f64, u64)> {
+        let mut items: Vec<(f64, u64)> = self.levels.iter().enumerate()
+            .flat_map(|(level, values)| values.iter().map(move |v| (*v, 1u64 << level)))
+            .collect();
+        items.sort_by(|a, b| a.0.total_cmp(&b.0));
+        items
+    }
+
+    fn capacity(&self, level: usize) -> usize {
+        let depth = (self.levels.len() - level - 1) as i32;
+        ((self.k as f64 * (2.0f64 / 3.0).powi(depth)).ceil() as usize).max(2)
+    }
+
+    fn compress(&mut self) {
+        loop {
+            let size: usize = self.levels.iter().map(Vec::len).sum();
+            let capacity: usize = (0..self.levels.len()).map(|level| self.capacity(level)).sum();
+            if size <= capacity {
+                return
+            }
+            let level = (0..self.levels.len()).find(|level| self.levels[*level].len() >= self.capacity(*level)).unwrap_or(0);
+            if level + 1 == self.levels.len() {
+                self.levels.push(vec![]);
+            }
+
+            let mut values = std::mem::take(&mut self.levels[level]);
+            values.sort_by(f64::total_cmp);
+            // an odd value stays on its level, so that the total weight is unchanged
+            if values.len() % 2 == 1 {
+                self.levels[level].push(values.pop().unwrap_or_default());
+            }
+            let offset = self.odd as usize;
+            self.odd = !self.odd;
+            let promoted = values.into_iter().skip(offset).step_by(2);
+            self.levels[level + 1].extend(promoted);
+        }
+    }
+}
+
+impl ReducerTrait for Quantiles {
+    type Input = f64;
+    type Reduced = Quantiles;
+
+    #[inline(always)]
+    fn next(&mut self, next: Self::Input) {
+        if next.is_nan() {
+            return
+        }
+        self.levels[0].push(next);
+        self.count += 1;
+        if self.levels[0].len() >= self.capacity(0) {
+            self.compress();
+        }
+    }
+
+    #[inline(always)]
+    fn finalize(self) -> Self::Reduced {
+        self
+    }
+}
+
+///
+/// DistinctCount
+///
+/// HyperLogLog estimate of the number of distinct inputs, e.g., payload templates. Uses ``2^precision`` bytes, the
+/// standard error is about ``1.04 / sqrt(2^precision)`` (``precision = 12``: 1.6%).
+///
+/// The reduced value is the sketch itself; sketches of partitions are combined with ``merge``.
+///
+#[derive(Clone, Debug)]
+pub struct DistinctCount<I> {
+    precision: u32,
+    registers: Vec<u8>,
+    phantom: PhantomData<I>,
+}
+
+impl<I> DistinctCount<I> {
+    pub fn new(precision: u32) -> Self {
+        assert!((4..=18).contains(&precision), "precision needs to be in 4..=18");
+        Self { precision, registers: vec![0; 1 << precision], phantom: PhantomData }
+    }
+
+    /// Estimated number of distinct inputs.
+    pub fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self.registers.iter().map(|r| 2f64.powi(-(*r as i32))).sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|r| **r == 0).count();
+        if estimate <= 2.5 * m && zeros > 0 {
+            // linear counting for small cardinalities
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+
+    /// Adds the inputs of ``other``, which needs the same precision.
+    pub fn merge(&mut self, other: DistinctCount<I>) {
+        assert_eq!(self.precision, other.precision, "merge needs sketches of the same precision");
+        for (r, o) in self.registers.iter_mut().zip(other.registers) {
+            *r = (*r).max(o);
+        }
+    }
+}
+
+impl<I> ReducerTrait for DistinctCount<I>
+where
+    I: Hash
+{
+    type Input = I;
+    type Reduced = DistinctCount<I>;
+
+    #[inline(always)]
+    fn next(&mut self, next: Self::Input) {
+        // DefaultHasher::new() uses fixed keys, so sketches of different threads can be merged
+        let mut hasher = DefaultHasher::new();
+        next.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let index = (hash >> (64 - self.precision)) as usize;
+        let rest = (hash << self.precision) | (1 << (self.precision - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    #[inline(always)]
+    fn finalize(self) -> Self::Reduced {
+        self
+    }
+}
+
+///
+/// TopK
+///
+/// Space-saving summary of the most frequent inputs, e.g., the noisiest APID/CTID. At most ``capacity`` keys are
+/// counted; a new key replaces the key with the lowest count and inherits that count as its error. Every key more
+/// frequent than ``n / capacity`` is contained.
+///
+/// The reduced value is the summary itself; summaries of partitions are combined with ``merge``.
+///
+#[derive(Clone, Debug)]
+pub struct TopK<Key> {
+    capacity: usize,
+    // key -> (count, error), the count overestimates the real count by at most error
+    counters: BTreeMap<Key, (u64, u64)>,
+}
+
+impl<Key> TopK<Key>
+where
+    Key: Ord + Clone
+{
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity needs to be positive");
+        Self { capacity, counters: BTreeMap::new() }
+    }
+
+    /// Keys with (count, error), sorted by descending count.
+    pub fn top(&self) -> Vec<(Key, u64, u64)> {
+        let mut top: Vec<(Key, u64, u64)> = self.counters.iter().map(|(k, (count, error))| (k.clone(), *count, *error)).collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top
+    }
+
+    /// Adds the inputs of ``other``. Keys missing in a full summary are estimated with its lowest count.
+    pub fn merge(&mut self, other: TopK<Key>) {
+        let min_self = self.min_count();
+        let min_other = other.min_count();
+        for (count, error) in self.counters.values_mut() {
+            *count += min_other;
+            *error += min_other;
+        }
+        for (key, (count, error)) in other.counters {
+            match self.counters.get_mut(&key) {
+                Some(counter) => {
+                    // min_other was added above as an estimate and is replaced by the real counter
+                    counter.0 = counter.0 - min_other + count;
+                    counter.1 = counter.1 - min_other + error;
+                },
+                None => {
+                    self.counters.insert(key, (count + min_self, error + min_self));
+                },
+            }
+        }
+
+        self.capacity = self.capacity.max(other.capacity);
+        let top = self.top();
+        self.counters = top.into_iter().take(self.capacity).map(|(k, count, error)| (k, (count, error))).collect();
+    }
+
+    fn min_count(&self) -> u64 {
+        if self.counters.len() < self.capacity {
+            return 0
+        }
+        self.counters.values().map(|(count, _)| *count).min().unwrap_or(0)
+    }
+}
+
+impl<Key> ReducerTrait for TopK<Key>
+where
+    Key: Ord + Clone
+{
+    type Input = Key;
+    type Reduced = TopK<Key>;
+
+    #[inline(always)]
+    fn next(&mut self, next: Self::Input) {
+        if let Some(counter) = self.counters.get_mut(&next) {
+            counter.0 += 1;
+        } else if self.counters.len() < self.capacity {
+            self.counters.insert(next, (1, 0));
+        } else {
+            // linear search of the minimum, the capacity is expected to be small
+            let min = self.counters.iter().min_by_key(|(_, (count, _))| *count).map(|(k, (count, _))| (k.clone(), *count));
+            if let Some((key, count)) = min {
+                self.counters.remove(&key);
+                self.counters.insert(next, (count + 1, count));
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn finalize(self) -> Self::Reduced {
+        self
+    }
+}
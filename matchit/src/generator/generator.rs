@@ -1,8 +1,11 @@
 use std::marker::PhantomData;
 use std::ops::Add;
 
+use super::approx::{DistinctCount, Quantiles, TopK};
 use super::groupby::GroupBy;
 use super::groupby::Merge;
+use super::sequence::Sequence;
+use super::window::Window;
 use super::reducer::*;
 use super::adapter::*;
 
@@ -87,5 +90,65 @@ impl<Input> Generator<Input> {
     {
         Adapter::new( Merge::new(f, keyfn), NilAdapter::new() )
     }
+
+    #[inline(always)]
+    pub fn window_tumbling<KeyFn,RedFn,Red,R>(keyfn: KeyFn, width: i64, reducerfn: RedFn) -> Adapter<Window<Input,KeyFn,RedFn,Red,R>, NilAdapter<Input>>
+    where
+        KeyFn: Fn(&Input) -> i64,
+        RedFn: Fn(&i64) -> Red,
+        Red: ReducerTrait<Input = Input, Reduced = R>,
+        Input: Clone
+    {
+        Adapter::new( Window::new(keyfn, width, width, reducerfn), NilAdapter::new() )
+    }
+
+    #[inline(always)]
+    pub fn window_sliding<KeyFn,RedFn,Red,R>(keyfn: KeyFn, width: i64, step: i64, reducerfn: RedFn) -> Adapter<Window<Input,KeyFn,RedFn,Red,R>, NilAdapter<Input>>
+    where
+        KeyFn: Fn(&Input) -> i64,
+        RedFn: Fn(&i64) -> Red,
+        Red: ReducerTrait<Input = Input, Reduced = R>,
+        Input: Clone
+    {
+        Adapter::new( Window::new(keyfn, width, step, reducerfn), NilAdapter::new() )
+    }
+
+    #[inline(always)]
+    #[allow(clippy::type_complexity)]
+    pub fn sequence<P1,P2,P3,KeyFn,TimeFn,Key>(start: P1, end: P2, cancel: P3, keyfn: KeyFn, timefn: TimeFn, within: i64) -> Adapter<Sequence<Input,P1,P2,P3,KeyFn,TimeFn,Key>, NilAdapter<Input>>
+    where
+        P1: Fn(&Input) -> bool,
+        P2: Fn(&Input) -> bool,
+        P3: Fn(&Input) -> bool,
+        KeyFn: Fn(&Input) -> Key,
+        TimeFn: Fn(&Input) -> i64,
+        Input: Clone,
+        Key: Ord
+    {
+        Adapter::new( Sequence::new(start, end, cancel, keyfn, timefn, within), NilAdapter::new() )
+    }
+
+    #[inline(always)]
+    pub fn distinct_count(precision: u32) -> Reducer<DistinctCount<Input>, NilAdapter<Input>>
+    where
+        Input: std::hash::Hash
+    {
+        Reducer::new ( DistinctCount::new(precision), NilAdapter::new() )
+    }
+
+    #[inline(always)]
+    pub fn top_k(capacity: usize) -> Reducer<TopK<Input>, NilAdapter<Input>>
+    where
+        Input: Ord + Clone
+    {
+        Reducer::new ( TopK::new(capacity), NilAdapter::new() )
+    }
 }
 
+impl Generator<f64> {
+    #[inline(always)]
+    pub fn quantiles(k: usize) -> Reducer<Quantiles, NilAdapter<f64>>
+    {
+        Reducer::new ( Quantiles::new(k), NilAdapter::new() )
+    }
+}
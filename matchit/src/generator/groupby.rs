@@ -1,6 +1,6 @@
 use std::{collections::BTreeMap, ops::ControlFlow};
 
-use super::adapter::{AdaptFnTrait};
+use super::{adapter::AdaptFnTrait, parallel::Mergeable, reducer::ReducerTrait};
 
 #[derive(Clone)]
 pub enum GroupByState<T>
@@ -22,6 +22,112 @@ where
     pub fn new(f: F) -> Self {
         Self (f, GroupByState::Empty)
     }
+
+    /// Takes the open group as (first, last), which is not emitted by ``finalize`` anymore.
+    pub fn take_open(&mut self) -> Option<(Input, Input)> {
+        match std::mem::replace(&mut self.1, GroupByState::Empty) {
+            GroupByState::Empty => None,
+            GroupByState::Consumed(prev) => Some((prev.clone(), prev)),
+            GroupByState::Lazy(group) => Some(group),
+        }
+    }
+}
+
+///
+/// GroupBySlice
+///
+/// Groups of one slice of a partitioned input: ``first`` and ``last`` are the groups at the borders of the slice,
+/// which may continue in the neighbouring slices, ``reduced`` contains the groups in between.
+/// If the slice contains a single group, it is ``first`` and ``last`` is ``None``.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupBySlice<T, R> {
+    pub first: Option<(T, T)>,
+    pub last: Option<(T, T)>,
+    pub reduced: R,
+}
+
+///
+/// ``groupby_slice``: groups the items of a slice like ``groupby(continues)`` and reduces the groups in between the
+/// border groups with ``reducer``. ``continues(a, a)`` needs to hold, so that every group is emitted.
+///
+pub fn groupby_slice<I, F, Red>(mut iter: I, continues: F, mut reducer: Red) -> GroupBySlice<I::Item, Red::Reduced>
+where
+    I: Iterator,
+    I::Item: Clone,
+    F: Fn(&I::Item, &I::Item) -> bool,
+    Red: ReducerTrait<Input = (I::Item, I::Item)>
+{
+    let Some(start) = iter.next() else {
+        return GroupBySlice { first: None, last: None, reduced: reducer.finalize() }
+    };
+    let mut first = (start.clone(), start);
+    let mut groupby = GroupBy::new(&continues);
+    for next in iter.by_ref() {
+        if continues(&first.1, &next) {
+            first.1 = next;
+        } else {
+            // the first group is complete, the next one starts
+            let _ = groupby.adapt(next);
+            break;
+        }
+    }
+    for next in iter {
+        if let ControlFlow::Continue(Some(group)) = groupby.adapt(next) {
+            reducer.next(group);
+        }
+    }
+    GroupBySlice { first: Some(first), last: groupby.take_open(), reduced: reducer.finalize() }
+}
+
+///
+/// ``stitch``: joins the ``GroupBySlice`` results of consecutive slices, so that the result equals the reduction of
+/// the groups of the whole input. A border group continues in the next slice if ``continues(last, first)`` holds for
+/// the last item of the group and the first item of the next slice. The completed border groups are reduced with
+/// reducers created by ``reducerfn`` and merged in order with the results of the slices.
+///
+pub fn stitch<T, F, RedFn, Red>(slices: impl IntoIterator<Item = GroupBySlice<T, Red::Reduced>>, continues: F, reducerfn: RedFn) -> Red::Reduced
+where
+    F: Fn(&T, &T) -> bool,
+    RedFn: Fn() -> Red,
+    Red: ReducerTrait<Input = (T, T)>,
+    Red::Reduced: Mergeable
+{
+    let mut result: Option<Red::Reduced> = None;
+    let mut push = |reduced: Red::Reduced| match result.as_mut() {
+        Some(acc) => acc.merge(reduced),
+        None => result = Some(reduced),
+    };
+
+    let mut open: Option<(T, T)> = None;
+    for slice in slices {
+        let mut border = reducerfn();
+        if let Some(first) = slice.first {
+            let first = match open.take() {
+                Some(prev) if continues(&prev.1, &first.0) => (prev.0, first.1),
+                Some(prev) => {
+                    border.next(prev);
+                    first
+                },
+                None => first,
+            };
+            open = match slice.last {
+                Some(last) => {
+                    border.next(first);
+                    Some(last)
+                },
+                None => Some(first),
+            };
+        }
+        push(border.finalize());
+        push(slice.reduced);
+    }
+    let mut border = reducerfn();
+    if let Some(prev) = open {
+        border.next(prev);
+    }
+    push(border.finalize());
+    result.unwrap_or_else(|| reducerfn().finalize())
 }
 
 impl<Input,F> AdaptFnTrait for GroupBy<Input,F>
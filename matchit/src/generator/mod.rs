@@ -1,7 +1,11 @@
 pub mod adapter;
+pub mod approx;
 pub mod reducer;
 pub mod generator;
 pub mod groupby;
+pub mod parallel;
+pub mod sequence;
+pub mod window;
 
 
 #[cfg(test)]
@@ -247,5 +251,170 @@ mod tests {
         println!("{:?}", split.finalize());
     }
 
+    #[test]
+    fn window_tumbling_generator() {
+        // (time, value)
+        let data = [(0, 1), (3, 2), (9, 3), (10, 4), (25, 5), (21, 6), (12, 7), (40, 8)];
+        let mut window = Generator::window_tumbling(|a: &(i64, usize)| a.0, 10, |_| Generator::map(|a: &(i64, usize)| a.1).sum()).collect();
+
+        for a in data {
+            window.next(a);
+        }
+        // (12, 7) is late, its window has been emitted
+        assert_eq!(vec![(0, 6), (10, 4), (20, 11), (40, 8)], window.finalize());
+    }
+
+    #[test]
+    fn window_sliding_adapter() {
+        let map = Generator::map(|a: &i64| *a * 2);
+        let mut window = map.window_sliding(|a: &i64| *a, 10, 5, |_| Generator::count()).collect();
+
+        for a in [0, 2, 4, 6, 8, 10, 12] {
+            window.next(a);
+        }
+        // 0..24 in steps of 4, windows [-5, 5), [0, 10), [5, 15), [10, 20), [15, 25), [20, 30)
+        assert_eq!(vec![(-5, 2), (0, 3), (5, 2), (10, 2), (15, 3), (20, 2)], window.finalize());
+    }
+
+    #[test]
+    fn window_split() {
+        // messages per window and key, like messages per second per APID
+        let mut split = Generator::split(|a: &(i64, char)| a.1,
+            |_| Generator::window_tumbling(|a: &(i64, char)| a.0, 1000, |_| Generator::count()).collect());
 
+        for a in [(0, 'a'), (10, 'b'), (999, 'a'), (1000, 'a'), (2500, 'b')] {
+            split.next(a);
+        }
+        let fin = split.finalize();
+        assert_eq!(vec![(0, 2), (1000, 1)], fin[&'a']);
+        assert_eq!(vec![(0, 1), (2000, 1)], fin[&'b']);
+    }
+
+    #[test]
+    fn sequence_generator() {
+        use sequence::SequenceEvent::*;
+        // (time, ECU, message): requests "req" followed by responses "rsp" within 500, "rst" cancels
+        let input = [(0, 'a', "req"), (100, 'b', "req"), (300, 'a', "rsp"), (400, 'b', "log"), (700, 'b', "rsp"),
+            (800, 'a', "req"), (900, 'a', "rst"), (950, 'a', "rsp"), (1000, 'b', "req")];
+        let mut events = Generator::sequence(|m: &(i32, char, &str)| m.2 == "req", |m| m.2 == "rsp", |m| m.2 == "rst",
+            |m| m.1, |m| m.0 as i64, 500).collect();
+        for m in input {
+            events.next(m);
+        }
+        assert_eq!(vec![
+            Matched(input[0], input[2]),
+            // the response of b comes too late
+            Timeout(input[1]),
+            Unmatched(input[4]),
+            Cancelled(input[5], input[6]),
+            Unmatched(input[7]),
+            Timeout(input[8]),
+        ], events.finalize());
+    }
+
+    #[test]
+    fn sequence_watchdog() {
+        use sequence::SequenceEvent::*;
+        // heartbeats are expected at most every 100, "stop" ends the supervision
+        let mut timeouts = Generator::filter(|m: &(i64, &str)| m.1 != "log")
+            .sequence(|m| m.1 == "alive", |m| m.1 == "alive", |m| m.1 == "stop", |_| (), |m| m.0, 100)
+            .filter(|e| matches!(e, Timeout(_)))
+            .collect();
+        for m in [(0, "alive"), (90, "alive"), (150, "log"), (180, "alive"), (400, "log"), (410, "alive"), (500, "alive"), (530, "stop")] {
+            timeouts.next(m);
+        }
+        assert_eq!(vec![Timeout((180, "alive"))], timeouts.finalize());
+    }
+
+    #[test]
+    fn approx_quantiles() {
+        const N: usize = 100000;
+        // values in a scrambled order, reduced in one piece and in 4 merged partitions
+        let values: Vec<f64> = (0..N).map(|i| ((i * 7919) % N) as f64).collect();
+        let mut single = Generator::quantiles(200);
+        values.iter().for_each(|v| single.next(*v));
+        let single = single.finalize();
+
+        let mut merged = approx::Quantiles::new(200);
+        for part in values.chunks(N / 4) {
+            let q = Generator::map(|v: &f64| *v).quantiles(200);
+            merged.merge(part.iter().copied().fold(q, |mut q, v| {q.next(v); q}).finalize());
+        }
+
+        for sketch in [single, merged] {
+            assert_eq!(N as u64, sketch.count());
+            for q in [0.01, 0.5, 0.9, 0.99] {
+                let value = sketch.quantile(q).unwrap();
+                assert!((value / N as f64 - q).abs() < 0.02, "q {} -> {}", q, value);
+            }
+            assert!((sketch.rank(N as f64 / 4.0) - 0.25).abs() < 0.02);
+        }
+        assert_eq!(None, approx::Quantiles::new(200).quantile(0.5));
+    }
+
+    #[test]
+    fn approx_distinct_count() {
+        let mut small = Generator::distinct_count(12);
+        for a in [1u32, 2, 3, 2, 1] {
+            small.next(a);
+        }
+        assert_eq!(3, small.finalize().estimate());
+
+        const N: u64 = 200000;
+        let mut merged = approx::DistinctCount::new(12);
+        for part in 0..4 {
+            // partitions overlap by half
+            let mut d = Generator::filter(|_: &u64| true).distinct_count(12);
+            for a in part * N / 8..part * N / 8 + N / 4 {
+                d.next(a);
+            }
+            merged.merge(d.finalize());
+        }
+        let estimate = merged.estimate() as f64;
+        let expected = (N / 8 * 5) as f64;
+        assert!((estimate / expected - 1.0).abs() < 0.05, "{}", estimate);
+    }
+
+    #[test]
+    fn approx_top_k() {
+        // key i occurs 2^(8-i) times, followed by noise of single occurrences
+        let stream: Vec<u32> = (0..8u32).flat_map(|i| std::iter::repeat_n(i, 1 << (8 - i)))
+            .chain(100..400).collect();
+
+        let mut top = Generator::top_k(16);
+        stream.iter().for_each(|a| top.next(*a));
+        let top = top.finalize().top();
+        assert_eq!(vec![0, 1, 2], top.iter().take(3).map(|(k, _, _)| *k).collect::<Vec<_>>());
+        assert!(top.iter().all(|(_, count, error)| count >= error));
+
+        let mut merged = approx::TopK::new(16);
+        for part in stream.chunks(100) {
+            let mut top = Generator::top_k(16);
+            part.iter().for_each(|a| top.next(*a));
+            merged.merge(top.finalize());
+        }
+        let top = merged.top();
+        assert_eq!(vec![0, 1, 2], top.iter().take(3).map(|(k, _, _)| *k).collect::<Vec<_>>());
+        // the counts overestimate by at most the error
+        for (key, count, error) in top.iter().take(3) {
+            let real = 1u64 << (8 - key);
+            assert!(*count >= real && count - error <= real);
+        }
+    }
+
+    #[test]
+    fn approx_top_k_merge_full() {
+        // both summaries are full and share "a", the error of "a" in other is below the minimum count of other
+        let mut other = Generator::top_k(2);
+        ["a", "b"].iter().for_each(|k| (0..5).for_each(|_| other.next(*k)));
+        let mut merged = Generator::top_k(2);
+        ["a", "c"].iter().for_each(|k| merged.next(*k));
+        let mut merged = merged.finalize();
+        merged.merge(other.finalize());
+
+        let top = merged.top();
+        assert_eq!(("a", 6, 0), top[0]);
+        assert_eq!(("b", 6, 1), top[1]);
+        assert_eq!(2, top.len());
+    }
 }
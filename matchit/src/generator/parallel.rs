@@ -0,0 +1,289 @@
+use std::{collections::BTreeMap, sync::atomic::{AtomicUsize, Ordering}, thread};
+
+use crate::WithOffset;
+
+use super::{approx::{DistinctCount, Quantiles, TopK}, groupby::{groupby_slice, stitch}, reducer::ReducerTrait};
+
+///
+/// Mergeable
+///
+/// Reduced values of partitions that can be combined into the reduced value of the whole input. ``merge`` is called
+/// in partition order, ``other`` is the result of the partition following ``self``.
+///
+pub trait Mergeable {
+    fn merge(&mut self, other: Self);
+}
+
+macro_rules! mergeable_sum {
+    ($($t:ty),*) => {
+        $(impl Mergeable for $t {
+            #[inline(always)]
+            fn merge(&mut self, other: Self) {
+                *self += other;
+            }
+        })*
+    };
+}
+
+mergeable_sum!(usize, u32, u64, i64, f64);
+
+/// Counts or results of ``Split``, values of the same key are merged.
+impl<K, V> Mergeable for BTreeMap<K, V>
+where
+    K: Ord,
+    V: Mergeable
+{
+    fn merge(&mut self, other: Self) {
+        for (k, v) in other {
+            match self.get_mut(&k) {
+                Some(value) => value.merge(v),
+                None => {
+                    self.insert(k, v);
+                },
+            }
+        }
+    }
+}
+
+/// Results of ``Fork``.
+impl<A, B> Mergeable for (A, B)
+where
+    A: Mergeable,
+    B: Mergeable
+{
+    fn merge(&mut self, other: Self) {
+        self.0.merge(other.0);
+        self.1.merge(other.1);
+    }
+}
+
+/// Collected values, in partition order.
+impl<T> Mergeable for Vec<T> {
+    fn merge(&mut self, other: Self) {
+        self.extend(other);
+    }
+}
+
+impl Mergeable for Quantiles {
+    fn merge(&mut self, other: Self) {
+        Quantiles::merge(self, other);
+    }
+}
+
+impl<I> Mergeable for DistinctCount<I> {
+    fn merge(&mut self, other: Self) {
+        DistinctCount::merge(self, other);
+    }
+}
+
+impl<Key> Mergeable for TopK<Key>
+where
+    Key: Ord + Clone
+{
+    fn merge(&mut self, other: Self) {
+        TopK::merge(self, other);
+    }
+}
+
+/// Applies ``f`` to each partition on at most ``threads`` worker threads, the results are in partition order.
+fn par_map<'bytes, F, R>(partitions: &[&'bytes [u8]], threads: usize, f: F) -> Vec<R>
+where
+    F: Fn(&'bytes [u8]) -> R + Sync,
+    R: Send
+{
+    let next = AtomicUsize::new(0);
+    let (f, next) = (&f, &next);
+    let mut results: Vec<(usize, R)> = thread::scope(|s| {
+        let handles: Vec<_> = (0..threads.max(1).min(partitions.len()))
+            .map(|_| s.spawn(move || {
+                let mut results = vec![];
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(slice) = partitions.get(index) else {
+                        break
+                    };
+                    results.push((index, f(slice)));
+                }
+                results
+            }))
+            .collect();
+        handles.into_iter().flat_map(|handle| handle.join().expect("partition thread panicked")).collect()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+///
+/// ``par_apply``: applies ``f`` to each partition on at most ``threads`` worker threads and merges the results in
+/// partition order. The workers take the partitions in order, so there can be more partitions than threads.
+///
+pub fn par_apply<'bytes, F, R>(partitions: &[&'bytes [u8]], threads: usize, f: F) -> Option<R>
+where
+    F: Fn(&'bytes [u8]) -> R + Sync,
+    R: Mergeable + Send
+{
+    let mut results = par_map(partitions, threads, f).into_iter();
+    let mut result = results.next()?;
+    for next in results {
+        result.merge(next);
+    }
+    Some(result)
+}
+
+///
+/// ``par_reduce``: reduces the items of each partition, read with ``iterfn``, with a reducer created by ``reducerfn``
+/// on at most ``threads`` worker threads and merges the reduced values in partition order, e.g.,
+///
+/// ``par_reduce(&partition_from::<T>(bytes, num), num, iterfn, || Generator::map(...).split(...))``
+///
+/// The result equals the sequential reduction if the pipeline does not carry state from one item to the next. For a
+/// ``groupby`` pipeline, use ``par_groupby`` or align the partitions to group boundaries with ``align_partitions``.
+///
+pub fn par_reduce<'bytes, T, I, IterFn, RedFn, Red>(partitions: &[&'bytes [u8]], threads: usize, iterfn: IterFn, reducerfn: RedFn) -> Red::Reduced
+where
+    IterFn: Fn(&'bytes [u8]) -> I + Sync,
+    I: Iterator<Item = T>,
+    RedFn: Fn() -> Red + Sync,
+    Red: ReducerTrait<Input = T>,
+    Red::Reduced: Mergeable + Send
+{
+    let reduce = |slice| {
+        let mut reducer = reducerfn();
+        iterfn(slice).for_each(|next| reducer.next(next));
+        reducer.finalize()
+    };
+    par_apply(partitions, threads, reduce).unwrap_or_else(|| reducerfn().finalize())
+}
+
+///
+/// ``par_groupby``: groups the items of each partition, read with ``iterfn``, like ``groupby(continues)`` on at most
+/// ``threads`` worker threads and reduces the groups with reducers created by ``reducerfn``. Groups spanning partition borders are joined
+/// with ``stitch``, so that the result equals the sequential ``groupby`` of all items followed by the reducer.
+/// ``continues(a, a)`` needs to hold.
+///
+pub fn par_groupby<'bytes, T, I, IterFn, F, RedFn, Red>(partitions: &[&'bytes [u8]], threads: usize, iterfn: IterFn, continues: F, reducerfn: RedFn) -> Red::Reduced
+where
+    IterFn: Fn(&'bytes [u8]) -> I + Sync,
+    I: Iterator<Item = T>,
+    T: Clone + Send,
+    F: Fn(&T, &T) -> bool + Sync,
+    RedFn: Fn() -> Red + Sync,
+    Red: ReducerTrait<Input = (T, T)>,
+    Red::Reduced: Mergeable + Send
+{
+    let slices = par_map(partitions, threads, |slice| groupby_slice(iterfn(slice), &continues, reducerfn()));
+    stitch(slices, &continues, &reducerfn)
+}
+
+///
+/// ``align_partitions``: moves the borders of ``partitions`` to group boundaries, so that no group of
+/// ``groupby(continues)`` spans two partitions. A border is moved forward to the first item ``b`` with
+/// ``!continues(a, b)`` for its predecessor ``a``; partitions that are swallowed by a group are dropped.
+///
+/// ``partitions`` are consecutive slices of ``bytes``, like the result of ``partition_from``, ``iterfn`` reads the
+/// items with their offset. Groups of keyed pipelines, e.g., a ``groupby`` within a ``split``, are not aligned.
+/// Like for ``par_groupby``, ``continues(a, a)`` needs to hold, otherwise ``groupby`` drops items that this pairwise
+/// view of the groups does not account for.
+///
+pub fn align_partitions<'bytes, T, I, IterFn, F>(bytes: &'bytes [u8], partitions: &[&'bytes [u8]], iterfn: IterFn, continues: F) -> Vec<&'bytes [u8]>
+where
+    IterFn: Fn(&'bytes [u8]) -> I,
+    I: Iterator<Item = WithOffset<T>>,
+    F: Fn(&T, &T) -> bool
+{
+    let mut borders = vec![0];
+    let mut start = 0;
+    for slice in partitions.iter().take(partitions.len().saturating_sub(1)) {
+        start += slice.len();
+        if start <= *borders.last().unwrap_or(&0) {
+            continue;
+        }
+        let mut prev: Option<T> = None;
+        let mut border = None;
+        for (offset, next) in iterfn(&bytes[start..]) {
+            if let Some(prev) = &prev {
+                if !continues(prev, &next) {
+                    border = Some(start + offset);
+                    break;
+                }
+            }
+            prev = Some(next);
+        }
+        match border {
+            Some(border) => borders.push(border),
+            None => break,
+        }
+    }
+    borders.push(bytes.len());
+    borders.windows(2).filter(|w| w[0] < w[1]).map(|w| &bytes[w[0]..w[1]]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fromgenerator::FromAdaptFnCall;
+    use crate::generator::{adapter::AdapterTrait, generator::Generator};
+
+    use super::*;
+
+    #[test]
+    fn par_reduce_split() {
+        let bytes: Vec<u8> = (0..10000u32).map(|i| (i * 31 % 251) as u8).collect();
+        let partitions: Vec<&[u8]> = bytes.chunks(1000).collect();
+
+        let histogram = |slice: &[u8]| slice.iter().split(|b| *b % 10, |_| Generator::count());
+        let expected = histogram(&bytes);
+        assert_eq!(Some(expected.clone()), par_apply(&partitions, 4, histogram));
+        assert_eq!(Some(expected.clone()), par_apply(&partitions, 1, histogram));
+
+        let result = par_reduce(&partitions, 4, |slice| slice.iter().copied(),
+            || Generator::fork(Generator::split(|b: &u8| *b % 10, |_| Generator::count()), Generator::count()));
+        assert_eq!((expected, bytes.len()), result);
+
+        let collected = par_reduce(&partitions, 4, |slice| slice.iter().copied(), || Generator::filter(|b: &u8| *b > 200).collect());
+        assert_eq!(bytes.iter().copied().filter(|b| *b > 200).collect::<Vec<u8>>(), collected);
+
+        assert_eq!(0, par_reduce(&[], 4, |slice: &[u8]| slice.iter(), Generator::count));
+    }
+
+    #[test]
+    fn par_reduce_aligned() {
+        // ascending runs of different lengths, the run lengths are counted
+        let bytes: Vec<u8> = (0..200u32).flat_map(|i| 0..(i % 37) as u8 + 1).collect();
+        let continues = |a: &u8, b: &u8| b > a;
+        let runs = |slice: &[u8]| slice.iter().copied().groupby(continues).map(|(a, b)| b - a).split(|len| *len, |_| Generator::count());
+        let expected = runs(&bytes);
+
+        let partitions: Vec<&[u8]> = bytes.chunks(bytes.len() / 7).collect();
+        assert_ne!(Some(expected.clone()), par_apply(&partitions, 4, runs));
+
+        let aligned = align_partitions(&bytes, &partitions, |slice| slice.iter().copied().enumerate(), continues);
+        assert_eq!(bytes.len(), aligned.iter().map(|slice| slice.len()).sum::<usize>());
+        assert!(aligned.iter().all(|slice| slice[0] == 0));
+        assert_eq!(Some(expected), par_apply(&aligned, 4, runs));
+
+        // a single group swallows all partitions
+        let bytes: Vec<u8> = (0..=255).collect();
+        let partitions: Vec<&[u8]> = bytes.chunks(10).collect();
+        assert_eq!(vec![bytes.as_slice()], align_partitions(&bytes, &partitions, |slice| slice.iter().copied().enumerate(), continues));
+    }
+
+    #[test]
+    fn par_groupby_stitched() {
+        // non-descending runs of different lengths, including runs longer than a partition
+        let bytes: Vec<u8> = (0..100u32).flat_map(|i| (0..(i * 7 % 97) as u8 + 1).map(|b| b / 2)).collect();
+        let continues = |a: &u8, b: &u8| b >= a;
+        let expected: Vec<(u8, u8)> = bytes.iter().copied().groupby(continues).collect();
+        let histogram = expected.iter().map(|(a, b)| b - a).split(|len| *len, |_| Generator::count());
+
+        for size in [1, 2, 3, 50, 97, 1000, bytes.len()] {
+            let partitions: Vec<&[u8]> = bytes.chunks(size).collect();
+            let runs = par_groupby(&partitions, 4, |slice| slice.iter().copied(), continues,
+                || Generator::map(|(a, b): &(u8, u8)| b - a).split(|len| *len, |_| Generator::count()));
+            assert_eq!(histogram, runs, "partition size {}", size);
+
+            let groups = par_groupby(&partitions, 4, |slice| slice.iter().copied(), continues, || Generator::map(|g: &(u8, u8)| *g).collect());
+            assert_eq!(expected, groups, "partition size {}", size);
+        }
+        assert_eq!(0, par_groupby(&[], 4, |slice: &[u8]| slice.iter().copied(), continues, Generator::count));
+    }
+}
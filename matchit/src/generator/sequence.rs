@@ -0,0 +1,131 @@
+use std::{collections::{BTreeMap, VecDeque}, ops::ControlFlow};
+
+use super::adapter::AdaptFnTrait;
+
+/// Event of a ``Sequence``.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SequenceEvent<T> {
+    /// A start followed by an end of the same key within the time limit.
+    Matched(T, T),
+    /// A start without end within the time limit, or without end at all.
+    Timeout(T),
+    /// A start followed by a cancel of the same key before its end.
+    Cancelled(T, T),
+    /// An end without pending start of the same key.
+    Unmatched(T),
+}
+
+///
+/// Sequence
+///
+/// Detects an input matching ``start``, followed by an input matching ``end`` with the same ``keyfn`` within
+/// ``within`` time units of ``timefn``, without an input matching ``cancel`` in between, e.g., a request followed by
+/// its response from the same ECU within 500 ms. Each key has its own state machine; pending starts of a key are
+/// matched first in, first out.
+///
+/// An input is checked for ``cancel``, then ``end`` and then ``start``, so an input matching ``end`` and ``start``
+/// completes the pending start and starts the next one, like the heartbeats of a watchdog.
+///
+/// The time advances with the latest time of all inputs; a start is emitted as ``Timeout`` as soon as this is more
+/// than ``within`` after the start. Starts pending at the end of the input are emitted as ``Timeout`` by ``finalize``.
+///
+pub struct Sequence<Input,P1,P2,P3,KeyFn,TimeFn,Key> {
+    start: P1,
+    end: P2,
+    cancel: P3,
+    keyfn: KeyFn,
+    timefn: TimeFn,
+    within: i64,
+    pending: BTreeMap<Key, VecDeque<(i64, Input)>>,
+    next_deadline: i64,
+    watermark: i64,
+    events: VecDeque<SequenceEvent<Input>>,
+}
+
+impl<Input,P1,P2,P3,KeyFn,TimeFn,Key> Sequence<Input,P1,P2,P3,KeyFn,TimeFn,Key> {
+    pub fn new(start: P1, end: P2, cancel: P3, keyfn: KeyFn, timefn: TimeFn, within: i64) -> Self {
+        Self { start, end, cancel, keyfn, timefn, within, pending: BTreeMap::new(), next_deadline: i64::MAX,
+            watermark: i64::MIN, events: VecDeque::new() }
+    }
+}
+
+impl<Input,P1,P2,P3,KeyFn,TimeFn,Key> Sequence<Input,P1,P2,P3,KeyFn,TimeFn,Key>
+where
+    Key: Ord
+{
+    /// Emits the starts that are more than ``within`` before ``self.watermark`` as ``Timeout``.
+    fn expire(&mut self) {
+        if self.watermark <= self.next_deadline {
+            return
+        }
+        let mut next_deadline = i64::MAX;
+        for starts in self.pending.values_mut() {
+            while let Some((time, _)) = starts.front() {
+                if self.watermark.saturating_sub(*time) <= self.within {
+                    break
+                }
+                if let Some((_, start)) = starts.pop_front() {
+                    self.events.push_back(SequenceEvent::Timeout(start));
+                }
+            }
+            if let Some((time, _)) = starts.front() {
+                next_deadline = next_deadline.min(time.saturating_add(self.within));
+            }
+        }
+        self.pending.retain(|_, starts| !starts.is_empty());
+        self.next_deadline = next_deadline;
+    }
+}
+
+impl<Input,P1,P2,P3,KeyFn,TimeFn,Key> AdaptFnTrait for Sequence<Input,P1,P2,P3,KeyFn,TimeFn,Key>
+where
+    P1: Fn(&Input) -> bool,
+    P2: Fn(&Input) -> bool,
+    P3: Fn(&Input) -> bool,
+    KeyFn: Fn(&Input) -> Key,
+    TimeFn: Fn(&Input) -> i64,
+    Input: Clone,
+    Key: Ord
+{
+    type Input = Input;
+    type Output = SequenceEvent<Input>;
+
+    #[inline(always)]
+    fn adapt(&mut self, next: Self::Input) -> ControlFlow<(), Option<Self::Output>> {
+        let time = (self.timefn)(&next);
+        self.watermark = self.watermark.max(time);
+        self.expire();
+
+        if (self.cancel)(&next) {
+            if let Some(starts) = self.pending.remove(&(self.keyfn)(&next)) {
+                for (_, start) in starts {
+                    self.events.push_back(SequenceEvent::Cancelled(start, next.clone()));
+                }
+            }
+        } else {
+            if (self.end)(&next) {
+                let event = match self.pending.get_mut(&(self.keyfn)(&next)).and_then(|starts| starts.pop_front()) {
+                    Some((_, start)) => SequenceEvent::Matched(start, next.clone()),
+                    None => SequenceEvent::Unmatched(next.clone()),
+                };
+                self.events.push_back(event);
+            }
+            if (self.start)(&next) {
+                self.next_deadline = self.next_deadline.min(time.saturating_add(self.within));
+                self.pending.entry((self.keyfn)(&next)).or_default().push_back((time, next));
+            }
+        }
+        ControlFlow::Continue(self.events.pop_front())
+    }
+
+    #[inline(always)]
+    fn finalize(&mut self) -> ControlFlow<(), Option<Self::Output>> {
+        while let Some((_, starts)) = self.pending.pop_first() {
+            self.events.extend(starts.into_iter().map(|(_, start)| SequenceEvent::Timeout(start)));
+        }
+        match self.events.pop_front() {
+            Some(event) => ControlFlow::Continue(Some(event)),
+            None => ControlFlow::Break(()),
+        }
+    }
+}
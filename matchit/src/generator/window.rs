@@ -0,0 +1,77 @@
+use std::{collections::{BTreeMap, VecDeque}, marker::PhantomData, ops::ControlFlow};
+
+use super::{adapter::AdaptFnTrait, reducer::ReducerTrait};
+
+///
+/// Window
+///
+/// Reduces the inputs of time windows ``[start, start + width)`` with one reducer per window, created by
+/// ``reducerfn(&start)``, and emits ``(start, reduced)`` for each window that received inputs. Window starts are
+/// multiples of ``step``: with ``step == width`` the windows are tumbling, with ``step < width`` they are sliding
+/// and an input is passed to each of the ``width / step`` windows containing it.
+///
+/// The time of an input is ``keyfn(&input)``. A window is emitted as soon as an input at or after its end is seen,
+/// the remaining windows are emitted by ``finalize``. Inputs may be out of order, except that inputs for an already
+/// emitted window are ignored.
+///
+pub struct Window<Input,KeyFn,RedFn,Red,R> {
+    keyfn: KeyFn,
+    reducerfn: RedFn,
+    width: i64,
+    step: i64,
+    open: BTreeMap<i64, Red>,
+    watermark: i64,
+    pending: VecDeque<(i64, R)>,
+    phantom: PhantomData<Input>,
+}
+
+impl<Input,KeyFn,RedFn,Red,R> Window<Input,KeyFn,RedFn,Red,R> {
+    pub fn new(keyfn: KeyFn, width: i64, step: i64, reducerfn: RedFn) -> Self {
+        assert!(width > 0 && step > 0, "window width and step need to be positive");
+        Self { keyfn, reducerfn, width, step, open: BTreeMap::new(), watermark: i64::MIN, pending: VecDeque::new(), phantom: PhantomData }
+    }
+}
+
+impl<Input,KeyFn,RedFn,Red,R> AdaptFnTrait for Window<Input,KeyFn,RedFn,Red,R>
+where
+    KeyFn: Fn(&Input) -> i64,
+    RedFn: Fn(&i64) -> Red,
+    Red: ReducerTrait<Input = Input, Reduced = R>,
+    Input: Clone
+{
+    type Input = Input;
+    type Output = (i64, R);
+
+    #[inline(always)]
+    fn adapt(&mut self, next: Self::Input) -> ControlFlow<(), Option<Self::Output>> {
+        let time = (self.keyfn)(&next);
+        self.watermark = self.watermark.max(time);
+
+        let mut start = time.div_euclid(self.step) * self.step;
+        while start > time - self.width && start + self.width > self.watermark {
+            let reducer = self.open.entry(start).or_insert_with(|| (self.reducerfn)(&start));
+            reducer.next(next.clone());
+            start -= self.step;
+        }
+
+        while let Some(entry) = self.open.first_entry() {
+            if *entry.key() + self.width > self.watermark {
+                break;
+            }
+            let (start, reducer) = entry.remove_entry();
+            self.pending.push_back((start, reducer.finalize()));
+        }
+        ControlFlow::Continue(self.pending.pop_front())
+    }
+
+    #[inline(always)]
+    fn finalize(&mut self) -> ControlFlow<(), Option<Self::Output>> {
+        while let Some((start, reducer)) = self.open.pop_first() {
+            self.pending.push_back((start, reducer.finalize()));
+        }
+        match self.pending.pop_front() {
+            Some(out) => ControlFlow::Continue(Some(out)),
+            None => ControlFlow::Break(()),
+        }
+    }
+}
@@ -2,6 +2,8 @@ pub mod searchable;
 pub mod generator;
 pub mod fromgenerator;
 pub mod readit;
+pub mod mergeit;
+pub mod parit;
 
 
 use std::{marker::PhantomData};
@@ -150,6 +152,15 @@ pub fn partition_from<'bytes, T: SearchableMarkerTrait<'bytes>>(bytes: &'bytes [
         }
     }
 
+    // the remaining bytes after the last split belong to the last partition
+    let covered: usize = result.iter().map(|slice| slice.len()).sum();
+    if covered < bytes.len() {
+        match result.last_mut() {
+            Some(last) => *last = &bytes[covered - last.len()..],
+            None => result.push(bytes),
+        }
+    }
+
     result
 
 }
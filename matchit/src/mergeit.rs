@@ -0,0 +1,88 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+///
+/// MergeIterator
+///
+/// Merges several iterators into one iterator ordered by the key returned by ``key(index, &item)``,
+/// where ``index`` is the index of the iterator the item belongs to.
+/// Returns values in the form
+///     (index, item)
+///
+/// Items with equal keys are returned in the order of the iterators, and the order of the items within each
+/// iterator is preserved. If the items of each iterator are ordered by key, the result is ordered by key as well.
+/// Only the next item of each iterator is kept in memory.
+///
+pub struct MergeIterator<I, K, F>
+    where I: Iterator
+{
+    iters: Vec<I>,
+    heads: Vec<Option<I::Item>>,
+    heap: BinaryHeap<Reverse<(K, usize)>>,
+    key: F,
+}
+
+impl<I, K, F> MergeIterator<I, K, F>
+    where
+        I: Iterator,
+        K: Ord,
+        F: FnMut(usize, &I::Item) -> K
+{
+    pub fn new(iters: Vec<I>, key: F) -> Self {
+        let heads = iters.iter().map(|_| None).collect();
+        let mut result = Self { iters, heads, heap: BinaryHeap::new(), key };
+        for index in 0..result.iters.len() {
+            result.advance(index);
+        }
+        result
+    }
+
+    fn advance(&mut self, index: usize) {
+        if let Some(item) = self.iters[index].next() {
+            self.heap.push(Reverse(((self.key)(index, &item), index)));
+            self.heads[index] = Some(item);
+        }
+    }
+}
+
+impl<I, K, F> Iterator for MergeIterator<I, K, F>
+    where
+        I: Iterator,
+        K: Ord,
+        F: FnMut(usize, &I::Item) -> K
+{
+    type Item = (usize, I::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((_, index)) = self.heap.pop()?;
+        let item = self.heads[index].take()?;
+        self.advance(index);
+        Some((index, item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_ordered() {
+        let a = vec![1, 4, 7, 10];
+        let b = vec![2, 3, 8];
+        let c: Vec<i32> = vec![];
+        let d = vec![0, 11];
+
+        let it = MergeIterator::new(vec![a.into_iter(), b.into_iter(), c.into_iter(), d.into_iter()], |_, v| *v);
+        let result: Vec<(usize, i32)> = it.collect();
+        assert_eq!(vec![(3, 0), (0, 1), (1, 2), (1, 3), (0, 4), (0, 7), (1, 8), (0, 10), (3, 11)], result);
+    }
+
+    #[test]
+    fn merge_stable() {
+        let a = vec![(1, 'a'), (1, 'b'), (2, 'c')];
+        let b = vec![(1, 'd'), (2, 'e')];
+
+        let it = MergeIterator::new(vec![a.into_iter(), b.into_iter()], |_, (k, _)| *k);
+        let result: String = it.map(|(_, (_, c))| c).collect();
+        assert_eq!("abdce", result);
+    }
+}
@@ -0,0 +1,153 @@
+use std::{collections::VecDeque, sync::{atomic::{AtomicBool, Ordering}, mpsc::{sync_channel, Receiver, SyncSender}, Mutex}, thread, vec};
+
+/// Number of items sent from a worker to the consumer at once.
+const BATCH_SIZE: usize = 1024;
+
+/// Partitions that were not taken by a worker yet, with their offset and the sender to the consumer.
+type Work<'bytes, R> = VecDeque<(usize, &'bytes [u8], SyncSender<Vec<R>>)>;
+
+///
+/// OrderedParIterator
+///
+/// Iterates over the results of partitions processed in parallel, in the order of the partitions and, within each
+/// partition, in the order produced by its stage. See ``par_iter_ordered``.
+///
+/// Dropping the iterator stops the workers. It borrows from ``par_iter_ordered`` and cannot be returned from the
+/// consumer, since workers waiting for it to consume their results would never finish.
+///
+pub struct OrderedParIterator<'a, R> {
+    receivers: VecDeque<Receiver<Vec<R>>>,
+    batch: vec::IntoIter<R>,
+    stop: &'a AtomicBool,
+}
+
+impl<R> Drop for OrderedParIterator<'_, R> {
+    fn drop(&mut self) {
+        // the receivers are dropped afterwards, which wakes up workers waiting to send
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<R> Iterator for OrderedParIterator<'_, R> {
+    type Item = R;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(next) = self.batch.next() {
+                return Some(next)
+            }
+            match self.receivers.front()?.recv() {
+                Ok(batch) => self.batch = batch.into_iter(),
+                // the partition is complete
+                Err(_) => {
+                    self.receivers.pop_front();
+                },
+            }
+        }
+    }
+}
+
+///
+/// ``par_iter_ordered``: processes ``partitions`` on ``threads`` worker threads and passes the results in their
+/// original order as ``OrderedParIterator`` to ``consumer``, e.g., to decode and filter messages in parallel and to
+/// write them in file order.
+///
+/// ``stage(offset, slice)`` returns the results of a partition, usually a chain of ``map`` and ``filter`` on the items
+/// of ``slice``. ``offset`` is the position of ``slice`` within the consecutive ``partitions``, like the result of
+/// ``partition_from``. The workers take the partitions in order, and each partition buffers at most ``capacity``
+/// batches of results that were not consumed yet. A worker with a full buffer waits for the consumer.
+///
+/// If ``consumer`` returns without consuming all results, the workers stop: partitions that were not taken yet are
+/// skipped, and a running stage is abandoned at its next result.
+///
+pub fn par_iter_ordered<'bytes, R, I, F, C, Out>(partitions: &[&'bytes [u8]], threads: usize, capacity: usize, stage: F, consumer: C) -> Out
+where
+    F: Fn(usize, &'bytes [u8]) -> I + Sync,
+    I: Iterator<Item = R>,
+    R: Send,
+    C: for<'a> FnOnce(OrderedParIterator<'a, R>) -> Out
+{
+    let mut offset = 0;
+    let mut receivers = VecDeque::new();
+    let mut work: Work<R> = VecDeque::new();
+    for slice in partitions {
+        let (sender, receiver) = sync_channel(capacity.max(1));
+        work.push_back((offset, *slice, sender));
+        receivers.push_back(receiver);
+        offset += slice.len();
+    }
+    let work = Mutex::new(work);
+    let stop = AtomicBool::new(false);
+
+    thread::scope(|s| {
+        for _ in 0..threads.max(1).min(partitions.len()) {
+            s.spawn(|| {
+                while !stop.load(Ordering::Relaxed) {
+                    let next = work.lock().expect("worker thread panicked").pop_front();
+                    let Some((offset, slice, sender)) = next else {
+                        break
+                    };
+                    let mut batch = Vec::with_capacity(BATCH_SIZE);
+                    for result in stage(offset, slice) {
+                        if stop.load(Ordering::Relaxed) {
+                            break
+                        }
+                        batch.push(result);
+                        if batch.len() == BATCH_SIZE && sender.send(std::mem::replace(&mut batch, Vec::with_capacity(BATCH_SIZE))).is_err() {
+                            // the consumer stopped
+                            break
+                        }
+                    }
+                    if !batch.is_empty() {
+                        let _ = sender.send(batch);
+                    }
+                }
+            });
+        }
+        consumer(OrderedParIterator { receivers, batch: vec![].into_iter(), stop: &stop })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordered() {
+        let bytes: Vec<u8> = (0..100000u32).map(|i| (i % 251) as u8).collect();
+        let partitions: Vec<&[u8]> = bytes.chunks(7000).collect();
+        let expected: Vec<(usize, u32)> = bytes.iter().enumerate()
+            .filter(|(_, b)| **b % 3 == 0).map(|(i, b)| (i, *b as u32 * 2)).collect();
+
+        fn stage(offset: usize, slice: &[u8]) -> impl Iterator<Item = (usize, u32)> + '_ {
+            slice.iter().enumerate()
+                .filter(|(_, b)| **b % 3 == 0)
+                .map(move |(i, b)| (offset + i, *b as u32 * 2))
+        }
+        for (threads, capacity) in [(1, 1), (4, 1), (4, 8), (32, 2)] {
+            let result: Vec<(usize, u32)> = par_iter_ordered(&partitions, threads, capacity, stage, |it| it.collect());
+            assert_eq!(expected, result);
+        }
+
+        // stops early without waiting for all partitions
+        let first = par_iter_ordered(&partitions, 4, 1, stage, |mut it| it.nth(10));
+        assert_eq!(Some(expected[10]), first);
+        assert_eq!(0, par_iter_ordered(&[], 4, 1, stage, |it| it.count()));
+    }
+
+    #[test]
+    fn ordered_stop() {
+        use std::sync::atomic::AtomicUsize;
+
+        let bytes: Vec<u8> = (0..1000000u32).map(|i| (i % 251) as u8).collect();
+        let partitions: Vec<&[u8]> = bytes.chunks(10000).collect();
+        let started = AtomicUsize::new(0);
+        let first = par_iter_ordered(&partitions, 4, 1, |_, slice| {
+            started.fetch_add(1, Ordering::Relaxed);
+            slice.iter().copied()
+        }, |mut it| it.next());
+        assert_eq!(Some(0), first);
+        // each worker waits on its first partition until the consumer returns, then no partition is taken anymore
+        assert!(started.load(Ordering::Relaxed) <= 4);
+    }
+}
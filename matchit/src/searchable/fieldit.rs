@@ -0,0 +1,97 @@
+use memchr::memmem;
+
+use crate::{ContainedBySearch, WithOffset};
+
+use super::SearchableMarkerTrait;
+
+///
+/// FieldSearchIterator
+///
+/// Searches ``pattern`` in a slice of raw bytes and returns the valid ``T`` whose header field contains it.
+/// Returns values in the form
+///     (offset, T)
+///
+/// For each match, the containing ``T`` is recovered with ``ContainedBySearch::contained_by``, which rejects
+/// ``T`` instances within the payload of another ``T``. ``is_field(&t, position)`` decides whether the match
+/// at ``position``, relative to the start of ``t``, is at the position of the searched field. Matches at other
+/// positions, e.g., within the payload, are discarded. The field must not overlap the marker of ``T``.
+///
+/// If the match is close behind the last known ``T``, e.g., the last returned ``T``, the containing ``T`` is found by
+/// reading forward from there instead. ``offset`` is expected to be the start of a ``T``.
+///
+/// This is faster than reading all ``T`` sequentially if the pattern is rare.
+///
+pub struct FieldSearchIterator<'bytes, T, F>
+{
+    offset: usize,
+    boundary: usize,
+    bytes: &'bytes [u8],
+    finder: memmem::Finder<'static>,
+    search: ContainedBySearch<T>,
+    is_field: F,
+}
+
+impl<'bytes, T, F> FieldSearchIterator<'bytes, T, F>
+where
+    T: SearchableMarkerTrait<'bytes>,
+    F: Fn(&T, usize) -> bool
+{
+    pub fn new(pattern: &[u8], bytes: &'bytes [u8], offset: usize, is_field: F) -> Self {
+        Self {
+            offset,
+            boundary: offset,
+            bytes,
+            finder: memmem::Finder::new(pattern).into_owned(),
+            search: ContainedBySearch::new(),
+            is_field,
+        }
+    }
+}
+
+impl<'bytes, T, F> FieldSearchIterator<'bytes, T, F>
+where
+    T: SearchableMarkerTrait<'bytes>
+{
+    /// Returns the ``T`` that contains ``[found, end)``.
+    fn container(&self, found: usize, end: usize) -> Option<WithOffset<T>> {
+        if found - self.boundary <= T::max_len() {
+            let mut pos = self.boundary;
+            while pos <= found {
+                match T::try_read(&self.bytes[pos..]) {
+                    Some((_, t)) if pos + t.len() >= end => return Some((pos, t)),
+                    Some((_, t)) => pos += t.len(),
+                    None => break,
+                }
+            }
+        }
+        self.search.contained_by(self.bytes, (found, end))
+    }
+}
+
+impl<'bytes, T, F> Iterator for FieldSearchIterator<'bytes, T, F>
+where
+    T: SearchableMarkerTrait<'bytes>,
+    F: Fn(&T, usize) -> bool
+{
+    type Item = WithOffset<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let found = self.offset + self.finder.find(self.bytes.get(self.offset..)?)?;
+            let end = found + self.finder.needle().len();
+
+            match self.container(found, end) {
+                Some((start, t)) if (self.is_field)(&t, found - start) => {
+                    self.offset = start + t.len();
+                    self.boundary = self.offset;
+                    return Some((start, t));
+                },
+                Some((start, _)) => {
+                    self.offset = found + 1;
+                    self.boundary = start;
+                },
+                None => self.offset = found + 1,
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::Range};
 
-use regex::bytes::Regex;
+use regex::bytes::{Regex, RegexSet};
 use crate::{TIterator, WithOffset};
 
 use super::{searchit::RevSearchIterator, SearchableMarkerTrait};
@@ -74,6 +74,171 @@ impl<'bytes, T> Iterator for GrepIterator<'bytes, T>
     }
 }
 
+///
+/// ScopedGrepIterator
+///
+/// Like ``GrepIterator``, but only accepts matches that lie fully inside a scope of the containing ``T``, e.g.,
+/// its payload. ``scope(&t)`` returns the scope relative to the start of ``t``, or ``None`` if ``t`` has no scope.
+/// The regex is still searched over the whole slice; matches outside of the scope, e.g., in headers, are discarded
+/// and the search continues behind them. A match that only partly lies inside the scope is searched again within
+/// the scope. Anchors like ``^`` refer to the whole slice, not to the scope.
+///
+pub struct ScopedGrepIterator<'bytes, T, F>
+{
+    offset : usize,
+    bytes: &'bytes [u8],
+    finder: Regex<>,
+    iter: RevSearchIterator<'bytes, T>,
+    scope: F,
+}
+
+impl<'bytes, T, F> ScopedGrepIterator<'bytes, T, F>
+where
+    T: SearchableMarkerTrait<'bytes>,
+    F: Fn(&T) -> Option<Range<usize>>
+{
+    pub fn new(pattern: &str, bytes: &'bytes [u8], offset: usize, scope: F) -> ScopedGrepIterator<'bytes, T, F> {
+        ScopedGrepIterator { offset, bytes,
+            finder: Regex::new(pattern).unwrap(),
+            iter: RevSearchIterator::<T>::new(bytes, offset),
+            scope,
+        }
+    }
+
+    pub fn search(&self, bytes: &'bytes [u8], mut offset: usize) -> Option<WithOffset<T>> {
+        while offset < bytes.len() {
+            let foundit = self.finder.find_at(bytes, offset)?.range();
+            // continue behind the start of the match, another match may start within it
+            offset = foundit.start + 1;
+
+            if let Some((_off, val)) = self.iter.search(bytes, foundit.start + 1) {
+                if let Some(scope) = (self.scope)(&val) {
+                    let scope = _off + scope.start..(_off + scope.end).min(bytes.len());
+                    if scope.start <= foundit.start && foundit.end <= scope.end {
+                        return Some((_off, val))
+                    }
+                    // a match starting before or running past the scope, e.g., a greedy ``.*``, may hide a match
+                    // within the scope
+                    if foundit.start < scope.end && self.finder.find_at(&bytes[..scope.end], foundit.start.max(scope.start)).is_some() {
+                        return Some((_off, val))
+                    }
+                    // no other match of the containing T lies within its scope
+                    offset = offset.max(_off + val.len());
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'bytes, T, F> Iterator for ScopedGrepIterator<'bytes, T, F>
+where
+    T: SearchableMarkerTrait<'bytes>,
+    F: Fn(&T) -> Option<Range<usize>>
+{
+    type Item = WithOffset<T>;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (offset, val) = self.search(self.bytes, self.offset)?;
+
+        // continue at the end of the entry in order not to emit it twice
+        self.offset = offset + val.len();
+
+        Some((offset, val))
+    }
+}
+
+///
+/// MultiGrepPatterns
+///
+/// Set of patterns compiled once for ``MultiGrepIterator``. Cloning is cheap, e.g., to search partitions in parallel.
+///
+#[derive(Clone, Debug)]
+pub struct MultiGrepPatterns {
+    any: Regex,
+    set: RegexSet,
+}
+
+impl MultiGrepPatterns {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self, regex::Error> {
+        let set = RegexSet::new(patterns)?;
+        let any = Regex::new(&patterns.iter().map(|p| format!("(?:{})", p.as_ref())).collect::<Vec<_>>().join("|"))?;
+        Ok(Self { any, set })
+    }
+
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+}
+
+///
+/// MultiGrepIterator
+///
+/// Searches many patterns in a single pass over a slice of raw bytes and returns the valid ``T`` containing a match
+/// together with the ids (indices) of all patterns that match within ``T``.
+/// Returns values in the form
+///     (offset, (T, pattern ids))
+///
+/// Like ``GrepIterator``, each ``T`` is returned at most once.
+///
+#[derive(Debug)]
+pub struct MultiGrepIterator<'bytes, T>
+{
+    offset : usize,
+    bytes: &'bytes [u8],
+    patterns: MultiGrepPatterns,
+    iter: RevSearchIterator<'bytes, T>
+}
+
+impl<'bytes, T> MultiGrepIterator<'bytes, T>
+where
+    T: SearchableMarkerTrait<'bytes>
+{
+    pub fn new(patterns: &MultiGrepPatterns, bytes: &'bytes [u8], offset: usize) -> MultiGrepIterator<'bytes, T> {
+        MultiGrepIterator { offset, bytes,
+            patterns: patterns.clone(),
+            iter: RevSearchIterator::<T>::new(bytes, offset)
+        }
+    }
+
+    pub fn search(&self, bytes: &'bytes [u8], mut offset: usize) -> Option<WithOffset<(T, Vec<usize>)>> {
+        while offset < bytes.len() {
+            let foundit = self.patterns.any.find_at(bytes, offset)?.range();
+            offset = foundit.start + 1;
+
+            if let Some((_off, val)) = self.iter.search(bytes, foundit.start + 1) {
+                if _off + val.len() >= foundit.end {
+                    let ids = self.patterns.set.matches(&bytes[_off.._off + val.len()]).into_iter().collect();
+                    return Some((_off, (val, ids)))
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'bytes, T> Iterator for MultiGrepIterator<'bytes, T>
+where
+    T: SearchableMarkerTrait<'bytes>
+{
+    type Item = WithOffset<(T, Vec<usize>)>;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (offset, (val, ids)) = self.search(self.bytes, self.offset)?;
+
+        // continue at the end of the entry in order not to emit it twice
+        self.offset = offset + val.len();
+
+        Some((offset, (val, ids)))
+    }
+}
+
 #[derive(Debug)]
 pub struct RegExMatcherIterator<'bytes, T>
 {
@@ -145,3 +310,106 @@ where
         Some((offset, val))
     }
 }
+
+/// Value of a capture group, parsed as integer or float if possible.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum CaptureValue<'bytes> {
+    Int(i64),
+    Float(f64),
+    Str(&'bytes [u8]),
+}
+
+impl<'bytes> CaptureValue<'bytes> {
+    pub fn parse(bytes: &'bytes [u8]) -> Self {
+        let text = std::str::from_utf8(bytes).unwrap_or_default();
+        if let Ok(value) = text.parse::<i64>() {
+            CaptureValue::Int(value)
+        } else if let Some(value) = parse_f64(text) {
+            CaptureValue::Float(value)
+        } else {
+            CaptureValue::Str(bytes)
+        }
+    }
+}
+
+/// Parses decimal numbers like ``-1.5e3``. Unlike ``str::parse``, words like ``inf`` or ``NaN`` are not numbers.
+pub fn parse_f64(text: &str) -> Option<f64> {
+    if !text.bytes().all(|b| b.is_ascii_digit() || matches!(b, b'+' | b'-' | b'.' | b'e' | b'E')) {
+        return None
+    }
+    text.parse().ok()
+}
+
+///
+/// NamedCaptureIterator
+///
+/// Searches a regex in a slice of raw bytes and returns the valid ``T`` containing the first match together with
+/// the values of the named capture groups of the match, in the order of ``NamedCaptureIterator::names``.
+/// Returns values in the form
+///     (offset, (T, values))
+///
+/// A value is ``None`` if its group did not participate in the match. Like ``GrepIterator``, each ``T`` is
+/// returned at most once.
+///
+#[derive(Debug)]
+pub struct NamedCaptureIterator<'bytes, T>
+{
+    offset : usize,
+    bytes: &'bytes [u8],
+    finder: Regex<>,
+    names: Vec<String>,
+    iter: RevSearchIterator<'bytes, T>
+}
+
+impl<'bytes, T> NamedCaptureIterator<'bytes, T>
+where
+    T: SearchableMarkerTrait<'bytes>
+{
+    pub fn new(pattern: &str, bytes: &'bytes [u8], offset: usize) -> NamedCaptureIterator<'bytes, T> {
+        let finder = Regex::new(pattern).unwrap();
+        let names = finder.capture_names().flatten().map(|name| name.to_string()).collect();
+        NamedCaptureIterator { offset, bytes, finder, names,
+            iter: RevSearchIterator::<T>::new(bytes, offset)
+        }
+    }
+
+    /// Names of the capture groups.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn capture(&self, bytes: &'bytes [u8], mut offset: usize) -> Option<WithOffset<(T, Vec<Option<CaptureValue<'bytes>>>)>> {
+        while offset < bytes.len() {
+            let captures = self.finder.captures_at(bytes, offset)?;
+            let foundit = captures.get(0)?.range();
+            offset = foundit.start + 1;
+
+            if let Some((_off, val)) = self.iter.search(bytes, foundit.start + 1) {
+                if _off + val.len() >= foundit.end {
+                    let values = self.names.iter()
+                        .map(|name| captures.name(name).map(|m| CaptureValue::parse(m.as_bytes())))
+                        .collect();
+                    return Some((_off, (val, values)))
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'bytes, T> Iterator for NamedCaptureIterator<'bytes, T>
+where
+    T: SearchableMarkerTrait<'bytes>
+{
+    type Item = WithOffset<(T, Vec<Option<CaptureValue<'bytes>>>)>;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (offset, (val, values)) = self.capture(self.bytes, self.offset)?;
+
+        // continue at the end of the entry in order not to emit it twice
+        self.offset = offset + val.len();
+
+        Some((offset, (val, values)))
+    }
+}
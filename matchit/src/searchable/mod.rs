@@ -1,3 +1,4 @@
+pub mod fieldit;
 pub mod grepit;
 pub mod readfallbackit;
 pub mod searchit;
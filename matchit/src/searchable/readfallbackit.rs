@@ -28,6 +28,12 @@ impl<'bytes, T> ReadFallbackIterator<'bytes, T> {
     pub fn new(bytes: &'bytes [u8], offset: usize) -> Self {
         Self { offset, bytes, phantom: PhantomData }
     }
+
+    /// Returns the offset of the next ``T`` to be read. After the iterator returned ``None``,
+    /// this is the offset of the first byte that could not be read as a valid ``T``.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
 }
 
 impl<'bytes, T> TIterator<'bytes> for ReadFallbackIterator<'bytes, T>
@@ -133,6 +133,11 @@ impl DltStorageHeader {
     fn size() -> usize {
         mem::size_of::<DltStorageHeader>()
     }
+
+    /// Storage time in microseconds since 1.1.1970.
+    pub fn timestamp_us(&self) -> i64 {
+        self.secs.get() as i64 * 1_000_000 + self.msecs.get() as i64
+    }
 }
 
 #[derive(AsBytes,FromBytes,FromZeroes,Debug)]
@@ -204,7 +209,7 @@ pub enum DltControlMessageTypeInfo {
 enum DltMessageInfoMask {
     Verbose = 0x1,
     MessageType = 0x7 << 1,
-    MessageTypeInfo = 0x15 << 4,
+    MessageTypeInfo = 0xF << 4,
 }
 
 #[derive(AsBytes,FromBytes,FromZeroes)]
@@ -281,7 +286,7 @@ impl DltExtendedHeader {
 }
 
 use zerocopy::FromBytes;
-use std::mem;
+use std::{io::{self, Write}, mem};
 
 use crate::DltIterator;
 
@@ -292,6 +297,15 @@ pub struct DltStorageEntry<'bytes> {
     pub dlt: DltEntry<'bytes>
 }
 
+impl DltStorageEntry<'_> {
+    /// Writes the message including its storage header to ``writer``.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), io::Error> {
+        writer.write_all(zerocopy::AsBytes::as_bytes(self.storage_header))?;
+        writer.write_all(zerocopy::AsBytes::as_bytes(self.dlt.header))?;
+        writer.write_all(self.dlt.tail)
+    }
+}
+
 #[inline(always)]
 fn try_read(bytes: &[u8]) -> Option<(usize, DltStorageEntry)> {
     let sh = DltStorageHeader::ref_from_prefix(bytes)?;
@@ -389,7 +403,7 @@ impl<'bytes> DltEntry<'bytes> {
     }
 
     #[inline(always)]
-    pub fn payload(&self) -> Option<&[u8]> {
+    pub fn payload(&self) -> Option<&'bytes [u8]> {
         let mut offset = 0usize;
         if self.header.header_type.is_with_ecu_id() {
             offset += 4;
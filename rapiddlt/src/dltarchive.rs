@@ -0,0 +1,182 @@
+use std::{fs::File, io::{self, BufReader, Read}};
+
+use glob::Pattern;
+use zip::ZipArchive;
+
+use crate::{dltbuffer::DltBuffer, dltcompress::{decompress, open_decoder, Compression}};
+
+const TAR_MAGIC_OFFSET: usize = 257;
+
+///
+/// ``DltArchiveMember``: a DLT file read from an archive, the member name is kept as provenance.
+///
+pub struct DltArchiveMember {
+    pub name: String,
+    pub buffer: DltBuffer,
+}
+
+///
+/// ``DltArchive``: reads DLT files directly from zip and tar archives without extracting them to disk.
+///
+/// Tar archives may be compressed with any format supported by ``dltcompress``. Archive members that are
+/// compressed themselves, e.g., ``*.dlt.gz``, are decompressed as well.
+///
+pub enum DltArchive {
+    Zip(ZipArchive<BufReader<File>>),
+    Tar(String),
+}
+
+impl DltArchive {
+    pub fn open(filename: &str) -> Result<Self, io::Error> {
+        let mut header = vec![];
+        open_decoder(filename)?.take(TAR_MAGIC_OFFSET as u64 + 5).read_to_end(&mut header)?;
+
+        if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            let zip = ZipArchive::new(BufReader::new(File::open(filename)?))?;
+            Ok(DltArchive::Zip(zip))
+        } else if header.get(TAR_MAGIC_OFFSET..) == Some(b"ustar") {
+            Ok(DltArchive::Tar(filename.to_string()))
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidData, "neither a zip nor a tar archive"))
+        }
+    }
+
+    /// Returns the names of all files in the archive.
+    pub fn names(&mut self) -> Result<Vec<String>, io::Error> {
+        let mut result = vec![];
+        self.for_each_entry(|name, _| {
+            result.push(name.to_string());
+            Ok(())
+        })?;
+        Ok(result)
+    }
+
+    /// Calls ``f`` for each file in the archive whose name matches the glob ``pattern``, e.g., ``*.dlt``.
+    /// Only one member is kept in memory at a time.
+    pub fn for_each_member<F>(&mut self, pattern: &str, mut f: F) -> Result<(), io::Error>
+    where
+        F: FnMut(DltArchiveMember)
+    {
+        let pattern = Pattern::new(pattern).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+        self.for_each_entry(|name, reader| {
+            if !pattern.matches(name) {
+                return Ok(());
+            }
+            let mut bytes = vec![];
+            reader.read_to_end(&mut bytes)?;
+            let buffer = match Compression::detect(&bytes) {
+                Compression::None => DltBuffer::Read(bytes),
+                compression => decompress(&bytes, compression)?,
+            };
+            f(DltArchiveMember { name: name.to_string(), buffer });
+            Ok(())
+        })
+    }
+
+    fn for_each_entry<F>(&mut self, mut f: F) -> Result<(), io::Error>
+    where
+        F: FnMut(&str, &mut dyn Read) -> Result<(), io::Error>
+    {
+        match self {
+            DltArchive::Zip(zip) => {
+                for i in 0..zip.len() {
+                    let mut file = zip.by_index(i)?;
+                    if file.is_file() {
+                        let name = file.name().to_string();
+                        f(&name, &mut file)?;
+                    }
+                }
+            },
+            DltArchive::Tar(filename) => {
+                let mut tar = tar::Archive::new(open_decoder(filename)?);
+                for entry in tar.entries()? {
+                    let mut entry = entry?;
+                    if entry.header().entry_type().is_file() {
+                        let name = entry.path()?.to_string_lossy().to_string();
+                        f(&name, &mut entry)?;
+                    }
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Seek, Write};
+
+    use flate2::{write::GzEncoder, Compression as GzLevel};
+    use tempfile::NamedTempFile;
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use crate::dlt_v1::dltit;
+
+    use super::*;
+
+    const FILES: [(&str, usize); 2] = [("lc_ex002.dlt", 11696), ("lc_ex003.dlt", 8045)];
+
+    fn counts(archive: &mut DltArchive, pattern: &str) -> Vec<(String, usize)> {
+        let mut result = vec![];
+        archive.for_each_member(pattern, |member| {
+            result.push((member.name, dltit(member.buffer.as_slice()).count()));
+        }).expect("valid archive");
+        result
+    }
+
+    fn tar(writer: impl Write) {
+        let mut builder = tar::Builder::new(writer);
+        for (name, _) in FILES {
+            builder.append_path_with_name(format!("../test/{}", name), format!("logs/{}", name)).unwrap();
+        }
+        builder.append_path_with_name("../test/test_gen.sh", "logs/test_gen.sh").unwrap();
+        builder.into_inner().unwrap();
+    }
+
+    #[test]
+    fn zip_members() {
+        let mut file = NamedTempFile::new().unwrap();
+        let mut zip = ZipWriter::new(file.as_file_mut());
+        for (name, _) in FILES {
+            zip.start_file(format!("logs/{}", name), SimpleFileOptions::default()).unwrap();
+            zip.write_all(&std::fs::read(format!("../test/{}", name)).unwrap()).unwrap();
+        }
+        zip.start_file("logs/lc_ex004.dlt.gz", SimpleFileOptions::default()).unwrap();
+        let mut gz = GzEncoder::new(vec![], GzLevel::default());
+        gz.write_all(&std::fs::read("../test/lc_ex004.dlt").unwrap()).unwrap();
+        zip.write_all(&gz.finish().unwrap()).unwrap();
+        zip.start_file("readme.txt", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"field logs").unwrap();
+        zip.finish().unwrap();
+        file.rewind().unwrap();
+
+        let mut archive = DltArchive::open(file.path().to_str().unwrap()).expect("valid zip archive");
+        assert_eq!(4, archive.names().unwrap().len());
+        assert_eq!(vec![("logs/lc_ex002.dlt".to_string(), 11696), ("logs/lc_ex003.dlt".to_string(), 8045)], counts(&mut archive, "*.dlt"));
+        assert_eq!(vec![("logs/lc_ex004.dlt.gz".to_string(), 52451)], counts(&mut archive, "logs/*.dlt.gz"));
+    }
+
+    #[test]
+    fn tar_members() {
+        let file = NamedTempFile::new().unwrap();
+        tar(file.as_file());
+
+        let mut archive = DltArchive::open(file.path().to_str().unwrap()).expect("valid tar archive");
+        assert_eq!(3, archive.names().unwrap().len());
+        let expected: Vec<(String, usize)> = FILES.iter().map(|(name, num)| (format!("logs/{}", name), *num)).collect();
+        assert_eq!(expected, counts(&mut archive, "logs/*.dlt"));
+    }
+
+    #[test]
+    fn tar_gz_members() {
+        let file = NamedTempFile::new().unwrap();
+        let mut gz = GzEncoder::new(file.as_file(), GzLevel::default());
+        tar(&mut gz);
+        gz.finish().unwrap();
+
+        let mut archive = DltArchive::open(file.path().to_str().unwrap()).expect("valid tar.gz archive");
+        assert_eq!(1, counts(&mut archive, "*lc_ex003*").len());
+        assert!(DltArchive::open("../test/lc_ex002.dlt").is_err());
+    }
+}
@@ -1,7 +1,10 @@
-use std::{fs::File, io::{self, BufReader, Read}};
+use std::{fs::File, io::{self, BufReader, Read}, mem};
 
 use memmap::MmapOptions;
-use matchit::{searchable::{SearchableMarkerTrait}, partition_from};
+use matchit::{searchable::{search_marker, SearchableMarkerTrait}, partition_from, FromBytesReadableTrait};
+
+use crate::{dlt_v1::{dltit_offset, DltStorageEntry, DltStorageHeader}, dltcompress::{decoder, decompress, Compression}};
+
 pub enum DltBuffer {
     Mmap(memmap::Mmap),
     Read(Vec<u8>)
@@ -12,12 +15,33 @@ impl DltBuffer {
         let mmap = unsafe { MmapOptions::new().map(&file)? };
         Ok(DltBuffer::Mmap(mmap))
     }
+    ///
+    /// ``open``: memory-maps ``filename``. Files compressed with gzip, zstd, xz or bzip2 are detected by their
+    /// magic bytes and decompressed into a temporary file, which is memory-mapped instead.
+    ///
+    pub fn open (filename: &str) -> Result<Self,io::Error> {
+        let buf = DltBuffer::mmap_file(filename)?;
+        match Compression::detect(buf.as_slice()) {
+            Compression::None => Ok(buf),
+            compression => decompress(buf.as_slice(), compression),
+        }
+    }
+    ///
+    /// ``read_file``: reads ``filename`` into memory. Like ``DltBuffer::open``, compressed files are decompressed.
+    ///
     pub fn read_file (filename: &str) -> Result<Self,io::Error> {
         let file = File::open(filename)?;
         let mut rdr = BufReader::new(file);
         let mut buf: Vec<u8> = vec![];
         let _ = rdr.read_to_end(&mut buf);
-        Ok(DltBuffer::Read(buf))
+        match Compression::detect(&buf) {
+            Compression::None => Ok(DltBuffer::Read(buf)),
+            compression => {
+                let mut decompressed = vec![];
+                decoder(buf.as_slice(), compression)?.read_to_end(&mut decompressed)?;
+                Ok(DltBuffer::Read(decompressed))
+            },
+        }
     }
     pub fn len(&self) -> usize {
         match self {
@@ -41,6 +65,28 @@ impl DltBuffer {
     }
 }
 
+///
+/// ``complete_prefix``: returns the length of the prefix of ``bytes`` that consists of complete DLT messages.
+/// 
+/// If the data following this prefix cannot become a valid DLT message anymore, even if more data is appended,
+/// the offset of the next candidate for a valid DLT message is returned as well.
+/// 
+pub(crate) fn complete_prefix(bytes: &[u8]) -> (usize, Option<usize>) {
+    let mut it = dltit_offset(bytes);
+    for _ in it.by_ref() {}
+    let end = it.offset();
+
+    if bytes.len() - end > DltStorageEntry::max_len() + mem::size_of::<DltStorageHeader>() {
+        let skip = match search_marker::<DltStorageEntry>(&bytes[end+1..]) {
+            Some(pos) => end + 1 + pos,
+            None => bytes.len() + 1 - DltStorageEntry::marker().len(),
+        };
+        (end, Some(skip))
+    } else {
+        (end, None)
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -92,6 +138,17 @@ mod tests {
     }
 
 
+    #[test]
+    fn partition_remainder() {
+        // the bytes after the last split were dropped if a partition ended shortly before the end
+        let buf = DltBuffer::read_file("../test/lc_ex002.dlt").expect("test file");
+        let slices = buf.partition::<DltStorageEntry>(64);
+        // ... and are not added as an extra partition
+        assert_eq!(64, slices.len());
+        assert_eq!(buf.as_slice().len(), slices.iter().map(|s| s.len()).sum::<usize>());
+        assert_eq!(dltit(buf.as_slice()).count(), slices.iter().map(|s| dltit(s).count()).sum::<usize>());
+    }
+
     #[test]
     fn partition_len() {
         let buf = DltBuffer::read_file("../test_gen/4_4gb_concat.dlt").expect("create files with test/test_gen.sh");
@@ -0,0 +1,276 @@
+use std::{fs::File, io::{self, BufReader, Read}, thread::{self, available_parallelism}};
+
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use memmap::MmapOptions;
+use xz2::read::XzDecoder;
+
+use crate::dltbuffer::DltBuffer;
+
+const ZSTD_SKIPPABLE_MAGIC: u32 = 0x184D2A5E;
+const ZSTD_SEEKABLE_MAGIC: u32 = 0x8F92EAB1;
+const ZSTD_SEEK_TABLE_FOOTER_SIZE: usize = 9;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Xz,
+    Bzip2,
+}
+
+impl Compression {
+    /// Detects the compression format by the magic bytes at the start of ``bytes``.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else if bytes.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Compression::Xz
+        } else if bytes.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Wraps ``reader`` in a streaming decoder for ``compression``.
+pub fn decoder<'a, R: Read + 'a>(reader: R, compression: Compression) -> Result<Box<dyn Read + 'a>, io::Error> {
+    Ok(match compression {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(MultiGzDecoder::new(reader)),
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(reader)?),
+        Compression::Xz => Box::new(XzDecoder::new_multi_decoder(reader)),
+        Compression::Bzip2 => Box::new(MultiBzDecoder::new(reader)),
+    })
+}
+
+/// Returns a streaming decoder of ``reader`` for the compression format detected by its first bytes,
+/// e.g., for stdin.
+pub fn detect_decoder<'a, R: Read + 'a>(mut reader: R) -> Result<Box<dyn Read + 'a>, io::Error> {
+    let mut magic = [0u8; 6];
+    let mut len = 0;
+    while len < magic.len() {
+        match reader.read(&mut magic[len..])? {
+            0 => break,
+            num => len += num,
+        }
+    }
+    let compression = Compression::detect(&magic[..len]);
+    decoder(BufReader::new(io::Cursor::new(magic[..len].to_vec()).chain(reader)), compression)
+}
+
+/// Opens ``filename`` and returns a streaming decoder for the detected compression format,
+/// e.g., to be used with ``DltStreamBuffer``.
+pub fn open_decoder(filename: &str) -> Result<Box<dyn Read>, io::Error> {
+    detect_decoder(File::open(filename)?)
+}
+
+///
+/// ``decompress``: decompresses ``bytes`` into a temporary file, which is memory-mapped.
+///
+/// Seekable zstd files, i.e., files with a seek table, are decoded frame by frame in parallel.
+///
+pub fn decompress(bytes: &[u8], compression: Compression) -> Result<DltBuffer, io::Error> {
+    let mut file = tempfile::tempfile()?;
+
+    match zstd_seek_table(bytes) {
+        Some(frames) if compression == Compression::Zstd && frames.len() > 1 => {
+            decompress_frames(bytes, &frames, &file)?;
+        },
+        _ => {
+            io::copy(&mut decoder(bytes, compression)?, &mut file)?;
+        },
+    }
+    let mmap = unsafe { MmapOptions::new().map(&file)? };
+    Ok(DltBuffer::Mmap(mmap))
+}
+
+/// Decodes the frames of a seekable zstd file in parallel into ``file``.
+fn decompress_frames(bytes: &[u8], frames: &[(usize, usize)], file: &File) -> Result<(), io::Error> {
+    let total: usize = frames.iter().map(|(_, decompressed)| decompressed).sum();
+    file.set_len(total as u64)?;
+    let mut mmap = unsafe { MmapOptions::new().map_mut(file)? };
+
+    // disjoint (source, destination) slices for each frame
+    let mut jobs = vec![];
+    let mut src = bytes;
+    let mut dst: &mut [u8] = &mut mmap;
+    for (compressed, decompressed) in frames {
+        if *compressed > src.len() || *decompressed > dst.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid zstd seek table"));
+        }
+        let (frame, rest) = src.split_at(*compressed);
+        let (out, rest_dst) = dst.split_at_mut(*decompressed);
+        jobs.push((frame, out));
+        src = rest;
+        dst = rest_dst;
+    }
+
+    let num = available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let chunk_size = jobs.len().div_ceil(num);
+    thread::scope(|s| {
+        let handles: Vec<_> = jobs.chunks_mut(chunk_size)
+            .map(|chunk| s.spawn(move || -> Result<(), io::Error> {
+                for (frame, out) in chunk.iter_mut() {
+                    if zstd::bulk::decompress_to_buffer(frame, out)? != out.len() {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, "zstd frame size does not match seek table"));
+                    }
+                }
+                Ok(())
+            }))
+            .collect();
+        handles.into_iter()
+            .try_for_each(|h| h.join().unwrap_or_else(|_| Err(io::Error::other("zstd decoder thread panicked"))))
+    })
+}
+
+///
+/// ``zstd_seek_table``: reads the seek table of a seekable zstd file, see the zstd seekable format specification.
+///
+/// Returns ``(compressed size, decompressed size)`` for each frame.
+///
+pub fn zstd_seek_table(bytes: &[u8]) -> Option<Vec<(usize, usize)>> {
+    let u32_at = |offset: usize| -> Option<u32> {
+        Some(u32::from_le_bytes(bytes.get(offset..offset+4)?.try_into().ok()?))
+    };
+
+    let footer = bytes.len().checked_sub(ZSTD_SEEK_TABLE_FOOTER_SIZE)?;
+    if u32_at(footer + 5)? != ZSTD_SEEKABLE_MAGIC {
+        return None
+    }
+    let num_frames = u32_at(footer)? as usize;
+    let entry_size = if bytes[footer + 4] & 0x80 > 0 { 12 } else { 8 };
+
+    let table_size = num_frames.checked_mul(entry_size)? + ZSTD_SEEK_TABLE_FOOTER_SIZE;
+    let table = bytes.len().checked_sub(table_size)?;
+    let skippable = table.checked_sub(8)?;
+    if u32_at(skippable)? != ZSTD_SKIPPABLE_MAGIC || u32_at(skippable + 4)? as usize != table_size {
+        return None
+    }
+
+    let frames: Option<Vec<(usize, usize)>> = (0..num_frames)
+        .map(|i| Some((u32_at(table + i*entry_size)? as usize, u32_at(table + i*entry_size + 4)? as usize)))
+        .collect();
+    let frames = frames?;
+    if frames.iter().map(|(compressed, _)| compressed).sum::<usize>() != skippable {
+        return None
+    }
+    Some(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression as GzLevel};
+    use tempfile::NamedTempFile;
+
+    use crate::{dlt_v1::{dltit, DltStorageEntry}, dltbuffer::DltBuffer, dltstream::DltStreamBuffer};
+
+    use super::*;
+
+    fn compressed(bytes: &[u8], compression: Compression) -> Vec<u8> {
+        match compression {
+            Compression::None => bytes.to_vec(),
+            Compression::Gzip => {
+                let mut e = GzEncoder::new(vec![], GzLevel::default());
+                e.write_all(bytes).unwrap();
+                e.finish().unwrap()
+            },
+            Compression::Zstd => zstd::encode_all(bytes, 0).unwrap(),
+            Compression::Xz => {
+                let mut e = xz2::write::XzEncoder::new(vec![], 6);
+                e.write_all(bytes).unwrap();
+                e.finish().unwrap()
+            },
+            Compression::Bzip2 => {
+                let mut e = bzip2::write::BzEncoder::new(vec![], bzip2::Compression::default());
+                e.write_all(bytes).unwrap();
+                e.finish().unwrap()
+            },
+        }
+    }
+
+    fn seekable(bytes: &[u8], frame_size: usize) -> Vec<u8> {
+        let mut result = vec![];
+        let mut table = vec![];
+        for chunk in bytes.chunks(frame_size) {
+            let frame = zstd::bulk::compress(chunk, 0).unwrap();
+            table.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            table.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            result.extend_from_slice(&frame);
+        }
+        let num_frames = table.len() / 8;
+        table.extend_from_slice(&(num_frames as u32).to_le_bytes());
+        table.push(0);
+        table.extend_from_slice(&ZSTD_SEEKABLE_MAGIC.to_le_bytes());
+
+        result.extend_from_slice(&ZSTD_SKIPPABLE_MAGIC.to_le_bytes());
+        result.extend_from_slice(&(table.len() as u32).to_le_bytes());
+        result.extend_from_slice(&table);
+        result
+    }
+
+    fn temp_file(bytes: &[u8]) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(bytes).unwrap();
+        file
+    }
+
+    #[test]
+    fn open_compressed() {
+        let plain = DltBuffer::mmap_file("../test/lc_ex002.dlt").expect("test file");
+
+        for compression in [Compression::None, Compression::Gzip, Compression::Zstd, Compression::Xz, Compression::Bzip2] {
+            let file = temp_file(&compressed(plain.as_slice(), compression));
+            let buf = DltBuffer::open(file.path().to_str().unwrap()).expect("valid compressed file");
+            assert_eq!(Compression::detect(&std::fs::read(file.path()).unwrap()), compression);
+            assert_eq!(plain.as_slice(), buf.as_slice(), "{:?}", compression);
+            assert_eq!(11696, dltit(buf.as_slice()).count());
+            let buf = DltBuffer::read_file(file.path().to_str().unwrap()).expect("valid compressed file");
+            assert_eq!(plain.as_slice(), buf.as_slice(), "{:?}", compression);
+        }
+    }
+
+    #[test]
+    fn stream_compressed() {
+        let plain = DltBuffer::mmap_file("../test/lc_ex004.dlt").expect("test file");
+
+        let count = |reader: Box<dyn Read>| {
+            let mut stream = DltStreamBuffer::new(reader);
+            let mut count = 0;
+            while let Some((_, slice)) = stream.next_slice().unwrap() {
+                count += dltit(slice).count();
+            }
+            count
+        };
+        for compression in [Compression::Gzip, Compression::Zstd, Compression::Xz, Compression::Bzip2] {
+            let bytes = compressed(plain.as_slice(), compression);
+            let file = temp_file(&bytes);
+            assert_eq!(52451, count(open_decoder(file.path().to_str().unwrap()).unwrap()), "{:?}", compression);
+            // a reader without file, like stdin
+            assert_eq!(52451, count(detect_decoder(io::Cursor::new(bytes)).unwrap()), "{:?}", compression);
+        }
+    }
+
+    #[test]
+    fn zstd_seekable() {
+        let plain = DltBuffer::mmap_file("../test/lc_ex005.dlt").expect("test file");
+        let bytes = seekable(plain.as_slice(), 100000);
+
+        let frames = zstd_seek_table(&bytes).expect("valid seek table");
+        assert_eq!(plain.len().div_ceil(100000), frames.len());
+        assert!(zstd_seek_table(&compressed(plain.as_slice(), Compression::Zstd)).is_none());
+
+        let file = temp_file(&bytes);
+        let buf = DltBuffer::open(file.path().to_str().unwrap()).expect("valid seekable zstd file");
+        assert_eq!(plain.as_slice(), buf.as_slice());
+
+        let count: usize = buf.partition::<DltStorageEntry>(4).iter().map(|slice| dltit(slice).count()).sum();
+        assert_eq!(40285, count);
+    }
+}
@@ -0,0 +1,214 @@
+use matchit::{searchable::{search_last_marker, SearchableMarkerTrait}, ContainedBySearch, FromBytesReadableTrait, WithOffset};
+
+use crate::{dlt_v1::{dltit_offset, DltStorageEntry}, dltindex::DltIndex, dltseek};
+
+///
+/// ``DltCursor``: moves forward and backward over the DLT messages of a buffer, e.g., for interactive viewers.
+///
+/// The cursor is positioned between two messages: ``DltCursor::next`` returns the message after the position,
+/// ``DltCursor::prev`` the message before it, so that ``next`` after ``prev`` returns the same message again.
+///
+/// Stepping backwards searches the previous DLT marker and uses ``ContainedBySearch::contained_by`` to reject markers
+/// within the payload of another message, like ``partition_from`` does.
+///
+pub struct DltCursor<'bytes> {
+    bytes: &'bytes [u8],
+    position: usize,
+    search: ContainedBySearch<DltStorageEntry<'bytes>>,
+}
+
+impl<'bytes> DltCursor<'bytes> {
+    /// Creates a cursor positioned before the first message of ``bytes``.
+    pub fn new(bytes: &'bytes [u8]) -> Self {
+        Self { bytes, position: 0, search: ContainedBySearch::new() }
+    }
+
+    /// Offset of the message returned by the next call of ``DltCursor::next``.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Moves the position to ``position``, which needs to be the offset of a message or the end of the buffer,
+    /// e.g., an offset returned by another iterator. Use ``DltCursor::seek_offset`` for arbitrary offsets.
+    pub fn set_position(&mut self, position: usize) {
+        self.position = position;
+    }
+
+    /// Returns the message after the position together with its offset and moves the position behind it.
+    /// Invalid data is skipped, including DLT markers that do not start a valid message.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<WithOffset<DltStorageEntry<'bytes>>> {
+        let mut start = self.position;
+        while start < self.bytes.len() {
+            let mut it = dltit_offset(&self.bytes[start..]);
+            if let Some((offset, entry)) = it.next() {
+                let offset = start + offset;
+                self.position = offset + entry.len();
+                return Some((offset, entry))
+            }
+            // the iterator stops at an invalid message, continue behind its marker
+            start += it.offset();
+            if !self.bytes[start..].starts_with(DltStorageEntry::marker()) {
+                break
+            }
+            start += 1;
+        }
+        None
+    }
+
+    /// Returns the message before the position together with its offset and moves the position before it.
+    /// Invalid data is skipped.
+    pub fn prev(&mut self) -> Option<WithOffset<DltStorageEntry<'bytes>>> {
+        let mut limit = self.position;
+        while let Some(candidate) = search_last_marker::<DltStorageEntry>(&self.bytes[..limit]) {
+            if let Some((_, entry)) = DltStorageEntry::try_read(&self.bytes[candidate..]) {
+                let marker = (candidate, candidate + DltStorageEntry::marker().len());
+                if candidate + entry.len() <= self.position && self.search.contained_by(self.bytes, marker).is_none() {
+                    self.position = candidate;
+                    return Some((candidate, entry));
+                }
+            }
+            limit = candidate;
+        }
+        None
+    }
+
+    /// Moves the position to the message that contains ``offset``. If ``offset`` points to invalid data,
+    /// the position is moved to the next valid message. Returns the new position.
+    pub fn seek_offset(&mut self, offset: usize) -> usize {
+        self.position = match self.search.contained_by(self.bytes, (offset, offset + 1)) {
+            Some((container, _)) => container,
+            None => dltseek::resync(&self.search, self.bytes, offset),
+        };
+        self.position
+    }
+
+    ///
+    /// ``seek_index``: moves the position to the message with number ``ordinal``. Returns the new position,
+    /// or ``None`` if there are not enough messages, in which case the position is at the end.
+    ///
+    /// With an ``index`` of the buffer, the cursor jumps to the closest indexed message before stepping forward.
+    ///
+    pub fn seek_index(&mut self, ordinal: usize, index: Option<&DltIndex>) -> Option<usize> {
+        let (mut current, position) = index
+            .and_then(|index| {
+                let records = index.records();
+                let i = records.partition_point(|r| r.ordinal.get() as usize <= ordinal).checked_sub(1)?;
+                Some((records[i].ordinal.get() as usize, records[i].offset.get() as usize))
+            })
+            .unwrap_or((0, 0));
+
+        self.position = position;
+        let mut offset = position;
+        while current <= ordinal {
+            // the offset of the returned message skips invalid data in front of it
+            match self.next() {
+                Some((next, _)) => offset = next,
+                None => {
+                    self.position = self.bytes.len();
+                    return None
+                },
+            }
+            current += 1;
+        }
+        self.position = offset;
+        Some(offset)
+    }
+
+    /// Moves the position to the first message with storage time at or after ``target_us``, see ``dltseek::seek_time``.
+    /// Returns the new position.
+    pub fn seek_time(&mut self, target_us: i64) -> usize {
+        self.position = dltseek::seek_time(self.bytes, target_us);
+        self.position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{dltbuffer::DltBuffer, dlttest::TestMessage};
+
+    use super::*;
+
+    fn message(payload: &[u8]) -> Vec<u8> {
+        TestMessage { extended: None, ..Default::default() }.bytes(payload)
+    }
+
+    #[test]
+    fn cursor_forward_backward() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex004.dlt").expect("test file");
+        let (end, _) = dltit_offset(buf.as_slice()).nth(3000).unwrap();
+        let bytes = &buf.as_slice()[..end];
+        let expected: Vec<usize> = dltit_offset(bytes).map(|(offset, _)| offset).collect();
+
+        let mut cursor = DltCursor::new(bytes);
+        let forward: Vec<usize> = std::iter::from_fn(|| cursor.next()).map(|(offset, _)| offset).collect();
+        assert_eq!(expected, forward);
+        assert_eq!(end, cursor.position());
+
+        let mut backward: Vec<usize> = std::iter::from_fn(|| cursor.prev()).map(|(offset, _)| offset).collect();
+        backward.reverse();
+        assert_eq!(expected, backward);
+        assert_eq!(0, cursor.position());
+    }
+
+    #[test]
+    fn cursor_messages_before() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex003.dlt").expect("test file");
+        let expected: Vec<usize> = dltit_offset(buf.as_slice()).map(|(offset, _)| offset).collect();
+
+        // show the 50 messages before message 1000, starting from an offset within message 1000
+        let mut cursor = DltCursor::new(buf.as_slice());
+        assert_eq!(expected[1000], cursor.seek_offset(expected[1000] + 10));
+        let mut before: Vec<usize> = (0..50).filter_map(|_| cursor.prev()).map(|(offset, _)| offset).collect();
+        before.reverse();
+        assert_eq!(&expected[950..1000], before.as_slice());
+        assert_eq!(Some(expected[950]), cursor.next().map(|(offset, _)| offset));
+    }
+
+    #[test]
+    fn cursor_nested_and_invalid() {
+        let inner = message(b"inner");
+        let outer = message(&[b"abc".as_slice(), &inner, b"xyz"].concat());
+        let last = message(b"last");
+        let bytes = [outer.as_slice(), b"garbage DLT", &last].concat();
+
+        let mut cursor = DltCursor::new(&bytes);
+        cursor.seek_offset(bytes.len());
+        assert_eq!(Some(outer.len() + 11), cursor.prev().map(|(offset, _)| offset));
+        // the message within the payload of the outer message is not returned
+        assert_eq!(Some(0), cursor.prev().map(|(offset, _)| offset));
+        assert!(cursor.prev().is_none());
+
+        assert_eq!(0, cursor.seek_offset(outer.len() - 5));
+        assert_eq!(outer.len() + 11, cursor.seek_offset(outer.len() + 2));
+
+        // a marker without valid message ends dltit_offset, but not the cursor
+        let bytes = [outer.as_slice(), b"DLT\x01", &[0xff; 20], &last].concat();
+        assert_eq!(1, dltit_offset(&bytes).count());
+        let mut cursor = DltCursor::new(&bytes);
+        let forward: Vec<usize> = std::iter::from_fn(|| cursor.next()).map(|(offset, _)| offset).collect();
+        assert_eq!(vec![0, outer.len() + 24], forward);
+        assert_eq!(Some(outer.len() + 24), cursor.prev().map(|(offset, _)| offset));
+    }
+
+    #[test]
+    fn cursor_seek_index() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex002.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        let expected: Vec<(usize, i64)> = dltit_offset(bytes).map(|(offset, e)| (offset, e.storage_header.timestamp_us())).collect();
+        let index = DltIndex::build(bytes, 64, 4);
+
+        let mut cursor = DltCursor::new(bytes);
+        for ordinal in [0, 1, 63, 64, 65, 5000, 11695] {
+            assert_eq!(Some(expected[ordinal].0), cursor.seek_index(ordinal, None));
+            assert_eq!(Some(expected[ordinal].0), cursor.seek_index(ordinal, Some(&index)));
+            assert_eq!(Some(expected[ordinal].0), cursor.next().map(|(offset, _)| offset));
+        }
+        assert_eq!(None, cursor.seek_index(11696, Some(&index)));
+        assert_eq!(bytes.len(), cursor.position());
+        assert!(cursor.next().is_none());
+
+        assert!(cursor.seek_time(expected[7000].1) <= expected[7000].0);
+        assert!(cursor.next().unwrap().1.storage_header.timestamp_us() >= expected[7000].1);
+    }
+}
@@ -0,0 +1,755 @@
+use std::io;
+
+use regex::bytes::Regex;
+
+use crate::dlt_v1::{DltMessageType, DltStorageEntry};
+
+const USECS_PER_DAY: i64 = 86_400_000_000;
+
+/// Header field holding a 4 byte ID.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum DltIdField {
+    Ecu,
+    Apid,
+    Ctid,
+}
+
+/// Comparison operator of ``level`` clauses.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum DltCmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl DltCmp {
+    fn eval<T: Ord>(&self, a: T, b: T) -> bool {
+        match self {
+            DltCmp::Eq => a == b,
+            DltCmp::Ne => a != b,
+            DltCmp::Lt => a < b,
+            DltCmp::Le => a <= b,
+            DltCmp::Gt => a > b,
+            DltCmp::Ge => a >= b,
+        }
+    }
+}
+
+///
+/// ``DltFilter``: predicate over ``DltStorageEntry``, parsed from a filter expression with ``DltFilter::parse``.
+///
+/// The payload is only accessed by ``DltFilter::Payload`` clauses. ``And`` and ``Or`` are evaluated from left to right
+/// and stop as soon as the result is known, ``DltFilter::and`` therefore moves clauses that access the payload to the
+/// end of a conjunction.
+///
+#[derive(Clone, Debug)]
+pub enum DltFilter {
+    True,
+    Not(Box<DltFilter>),
+    And(Vec<DltFilter>),
+    Or(Vec<DltFilter>),
+    /// The ID is one of the given IDs, IDs shorter than 4 bytes are padded with zeros.
+    /// The ECU ID of the standard header is preferred over the one of the storage header.
+    Id(DltIdField, Vec<[u8; 4]>),
+    /// The ID without its padding zeros matches the regex, like the regex ID filters of dlt-viewer.
+    IdRegex(DltIdField, Regex),
+    /// Log level of log messages, ``1`` (fatal) to ``6`` (verbose). Other messages do not match.
+    Level(DltCmp, u8),
+    /// Message type, ``0`` (log), ``1`` (app trace), ``2`` (network trace) or ``3`` (control).
+    MessageType(u8),
+    Verbose(bool),
+    Payload(Regex),
+    /// Storage time in ``[from, to]``, in microseconds since 1.1.1970.
+    StorageTime(i64, i64),
+    /// Time of day (UTC) of the storage time in ``[from, to]``, in microseconds since midnight.
+    TimeOfDay(i64, i64),
+}
+
+impl DltFilter {
+    ///
+    /// ``parse``: parses a filter expression, e.g.,
+    /// ``ecu == "ECU1" && apid in ["NAV","HMI"] && level <= warn && payload =~ /timeout/ && time in [10:02:00, 10:05:00]``.
+    ///
+    /// Clauses:
+    /// - ``ecu``, ``apid``, ``ctid``: ``== "ID"``, ``!= "ID"``, ``in ["ID", ...]``
+    /// - ``level``: ``==``, ``!=``, ``<``, ``<=``, ``>``, ``>=`` with ``fatal``, ``error``, ``warn``, ``info``, ``debug``, ``verbose``
+    ///   or ``1`` to ``6``, a lower level is more severe
+    /// - ``type``: ``==``, ``!=`` with ``log``, ``app_trace``, ``nw_trace``, ``control``
+    /// - ``verbose``: ``== true``, ``== false``
+    /// - ``payload``: ``=~ /regex/``, ``!~ /regex/``, ``contains "text"``
+    /// - ``time``: ``in [from, to]``, ``<``, ``<=``, ``>``, ``>=`` with seconds since 1.1.1970, e.g., ``1699355353.65``,
+    ///   or a time of day in UTC, e.g., ``10:02:00.5``
+    ///
+    /// Clauses are combined with ``&&``, ``||``, ``!`` and parentheses.
+    ///
+    pub fn parse(expr: &str) -> Result<Self, io::Error> {
+        let tokens = tokenize(expr)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let filter = parser.or()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some(_) => Err(parser.error("unexpected token")),
+        }
+    }
+
+    /// Conjunction of ``filters``, which evaluates the filters that access the payload last.
+    pub fn and(mut filters: Vec<DltFilter>) -> Self {
+        filters.sort_by_key(|f| f.reads_payload());
+        DltFilter::And(filters)
+    }
+
+    /// Returns ``true`` if evaluating the filter may access the payload.
+    fn reads_payload(&self) -> bool {
+        match self {
+            DltFilter::Payload(_) => true,
+            DltFilter::Not(f) => f.reads_payload(),
+            DltFilter::And(fs) | DltFilter::Or(fs) => fs.iter().any(|f| f.reads_payload()),
+            _ => false,
+        }
+    }
+
+    /// Returns ``true`` if ``entry`` matches the filter.
+    pub fn matches(&self, entry: &DltStorageEntry) -> bool {
+        match self {
+            DltFilter::True => true,
+            DltFilter::Not(f) => !f.matches(entry),
+            DltFilter::And(fs) => fs.iter().all(|f| f.matches(entry)),
+            DltFilter::Or(fs) => fs.iter().any(|f| f.matches(entry)),
+            DltFilter::Id(field, ids) => id(*field, entry).is_some_and(|id| ids.contains(&id)),
+            DltFilter::IdRegex(field, regex) => id(*field, entry).is_some_and(|id| {
+                let len = id.iter().position(|b| *b == 0).unwrap_or(id.len());
+                regex.is_match(&id[..len])
+            }),
+            DltFilter::Level(cmp, level) => {
+                match entry.dlt.extended_header().and_then(|ext| ext.msin.message_type()) {
+                    Some(DltMessageType::DltTypeLog(info)) => cmp.eval(info as u8, *level),
+                    _ => false,
+                }
+            },
+            DltFilter::MessageType(typ) => {
+                entry.dlt.extended_header().and_then(|ext| ext.msin.message_type()).is_some_and(|t| message_type_id(&t) == *typ)
+            },
+            DltFilter::Verbose(verbose) => {
+                entry.dlt.extended_header().is_some_and(|ext| ext.msin.is_verbose() == *verbose)
+            },
+            DltFilter::Payload(regex) => entry.dlt.payload().is_some_and(|payload| regex.is_match(payload)),
+            DltFilter::StorageTime(from, to) => (*from..=*to).contains(&entry.storage_header.timestamp_us()),
+            DltFilter::TimeOfDay(from, to) => (*from..=*to).contains(&entry.storage_header.timestamp_us().rem_euclid(USECS_PER_DAY)),
+        }
+    }
+
+    ///
+    /// ``from_dlf``: converts the filters of a dlt-viewer ``.dlf`` file into a ``DltFilter``.
+    ///
+    /// Like in dlt-viewer, a message is selected if it matches any enabled positive filter, or if there is no enabled
+    /// positive filter, and if it matches no enabled negative filter. Marker filters are ignored.
+    /// A single filter matches if all its enabled criteria match: ECU/APID/CTID (APID and CTID optionally as regex),
+    /// payload text (optionally as regex and case insensitive), minimum and maximum log level, which only restrict log
+    /// messages, and control messages. Header text and message ID criteria are not supported.
+    ///
+    /// Both generations of the format are read: current dlt-viewer versions write ``enableregex_Appid``,
+    /// ``enableregex_Context`` and ``enableregex_Payload``, older versions a single ``enableregex`` for all fields.
+    ///
+    pub fn from_dlf(xml: &str) -> Result<Self, io::Error> {
+        let invalid = |e: &dyn std::fmt::Display| io::Error::new(io::ErrorKind::InvalidData, format!("dlf: {}", e));
+        let doc = roxmltree::Document::parse(xml).map_err(|e| invalid(&e))?;
+
+        let mut positive = vec![];
+        let mut negative = vec![];
+        for node in doc.root_element().children().filter(|n| n.has_tag_name("filter")) {
+            let text = |tag: &str| node.children().find(|n| n.has_tag_name(tag)).and_then(|n| n.text()).unwrap_or("").trim();
+            let enabled = |tag: &str| text(tag) == "1";
+            let level = |tag: &str| text(tag).parse::<u8>().map_err(|e| invalid(&format!("{}: {}", tag, e)));
+            let regex = |tag: &str| enabled(tag) || enabled("enableregex");
+            // like dlt-viewer, level criteria do not exclude other message types
+            let log_level = |cmp, level| DltFilter::Or(vec![DltFilter::Not(Box::new(DltFilter::MessageType(0))), DltFilter::Level(cmp, level)]);
+
+            if !enabled("enablefilter") {
+                continue;
+            }
+            if enabled("enableheadertext") {
+                return Err(invalid(&"header text filters are not supported"));
+            }
+            if enabled("enableMessageId") {
+                return Err(invalid(&"message ID filters are not supported"));
+            }
+
+            let mut clauses = vec![];
+            if enabled("enableecuid") {
+                clauses.push(DltFilter::Id(DltIdField::Ecu, vec![id_from_str(text("ecuid"))?]));
+            }
+            for (tag, enable, regex_tag, field) in [("applicationid", "enableapplicationid", "enableregex_Appid", DltIdField::Apid),
+                                                    ("contextid", "enablecontextid", "enableregex_Context", DltIdField::Ctid)] {
+                if enabled(enable) && regex(regex_tag) {
+                    clauses.push(DltFilter::IdRegex(field, Regex::new(text(tag)).map_err(|e| invalid(&e))?));
+                } else if enabled(enable) {
+                    clauses.push(DltFilter::Id(field, vec![id_from_str(text(tag))?]));
+                }
+            }
+            if enabled("enableLogLevelMax") {
+                clauses.push(log_level(DltCmp::Le, level("logLevelMax")?));
+            }
+            if enabled("enableLogLevelMin") {
+                clauses.push(log_level(DltCmp::Ge, level("logLevelMin")?));
+            }
+            if enabled("enablectrlmsgs") {
+                clauses.push(DltFilter::MessageType(3));
+            }
+            if enabled("enablepayloadtext") {
+                let pattern = if regex("enableregex_Payload") { text("payloadtext").to_string() } else { regex::escape(text("payloadtext")) };
+                let pattern = if enabled("ignoreCase_Payload") { format!("(?i){}", pattern) } else { pattern };
+                clauses.push(DltFilter::Payload(Regex::new(&pattern).map_err(|e| invalid(&e))?));
+            }
+
+            match text("type") {
+                "0" => positive.push(DltFilter::and(clauses)),
+                "1" => negative.push(DltFilter::and(clauses)),
+                _ => {},
+            }
+        }
+
+        let mut result = vec![];
+        if !positive.is_empty() {
+            result.push(DltFilter::Or(positive));
+        }
+        if !negative.is_empty() {
+            result.push(DltFilter::Not(Box::new(DltFilter::Or(negative))));
+        }
+        Ok(if result.is_empty() { DltFilter::True } else { DltFilter::and(result) })
+    }
+
+    /// Reads a dlt-viewer ``.dlf`` file, see ``DltFilter::from_dlf``.
+    pub fn open_dlf(filename: &str) -> Result<Self, io::Error> {
+        Self::from_dlf(&std::fs::read_to_string(filename)?)
+    }
+
+    /// Returns the filter as closure, e.g., to be used with ``Iterator::filter``.
+    pub fn predicate(&self) -> impl Fn(&DltStorageEntry) -> bool + '_ {
+        move |entry| self.matches(entry)
+    }
+
+    /// Returns a single ID that all matching messages have, e.g., to find candidates with ``dltidsearch::id_search``.
+    pub fn required_id(&self) -> Option<(DltIdField, [u8; 4])> {
+        match self {
+            DltFilter::Id(field, ids) if ids.len() == 1 => Some((*field, ids[0])),
+            DltFilter::And(filters) => filters.iter().find_map(|f| f.required_id()),
+            _ => None,
+        }
+    }
+}
+
+fn id(field: DltIdField, entry: &DltStorageEntry) -> Option<[u8; 4]> {
+    match field {
+        DltIdField::Ecu => Some(entry.dlt.ecu_id().map(|id| id.to_be_bytes()).unwrap_or(entry.storage_header.ecu)),
+        DltIdField::Apid => entry.dlt.extended_header().map(|ext| ext.apid),
+        DltIdField::Ctid => entry.dlt.extended_header().map(|ext| ext.ctid),
+    }
+}
+
+fn message_type_id(typ: &DltMessageType) -> u8 {
+    match typ {
+        DltMessageType::DltTypeLog(_) => 0,
+        DltMessageType::DltTypeAppTrace(_) => 1,
+        DltMessageType::DltTypeNwTrace(_) => 2,
+        DltMessageType::DltTypeControl(_) => 3,
+    }
+}
+
+/// Converts ``id`` into a 4 byte ID padded with zeros.
+pub fn id_from_str(id: &str) -> Result<[u8; 4], io::Error> {
+    if id.len() > 4 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("ID '{}' is longer than 4 bytes", id)));
+    }
+    let mut result = [0u8; 4];
+    result[..id.len()].copy_from_slice(id.as_bytes());
+    Ok(result)
+}
+
+#[derive(PartialEq, Clone, Debug)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Regex(String),
+    Number(String),
+    Op(&'static str),
+}
+
+const OPERATORS: [&str; 16] = ["==", "!=", "<=", ">=", "=~", "!~", "&&", "||", "<", ">", "!", "(", ")", "[", "]", ","];
+
+fn tokenize(expr: &str) -> Result<Vec<(usize, Token)>, io::Error> {
+    let bytes = expr.as_bytes();
+    let mut tokens = vec![];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let c = bytes[pos];
+        let start = pos;
+        if c.is_ascii_whitespace() {
+            pos += 1;
+            continue;
+        }
+        let token = if c == b'"' || c == b'/' {
+            let (text, end) = quoted(expr, pos, c)?;
+            pos = end;
+            if c == b'"' { Token::Str(text) } else { Token::Regex(text) }
+        } else if c.is_ascii_digit() || c == b'-' {
+            pos += 1;
+            while pos < bytes.len() && (bytes[pos].is_ascii_digit() || bytes[pos] == b'.' || bytes[pos] == b':') {
+                pos += 1;
+            }
+            Token::Number(expr[start..pos].to_string())
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            while pos < bytes.len() && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_') {
+                pos += 1;
+            }
+            Token::Ident(expr[start..pos].to_string())
+        } else {
+            let op = OPERATORS.iter().find(|op| expr[pos..].starts_with(**op))
+                .ok_or_else(|| parse_error(pos, "unexpected character"))?;
+            pos += op.len();
+            Token::Op(op)
+        };
+        tokens.push((start, token));
+    }
+    tokens.push((expr.len(), Token::Op("")));
+    Ok(tokens)
+}
+
+/// Reads a string or regex delimited by ``delimiter``, which can be escaped with a backslash.
+fn quoted(expr: &str, start: usize, delimiter: u8) -> Result<(String, usize), io::Error> {
+    let mut result = String::new();
+    let mut chars = expr[start + 1..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, c)) if c as u32 == delimiter as u32 => result.push(c),
+                // keep escape sequences of regexes
+                Some((_, c)) if delimiter == b'/' => { result.push('\\'); result.push(c); },
+                Some((_, c)) => result.push(c),
+                None => break,
+            },
+            c if c as u32 == delimiter as u32 => return Ok((result, start + 1 + i + 1)),
+            c => result.push(c),
+        }
+    }
+    Err(parse_error(start, "unterminated string"))
+}
+
+fn parse_error(pos: usize, message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("filter: {} at position {}", message, pos))
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        match &self.tokens[self.pos].1 {
+            Token::Op("") => None,
+            token => Some(token),
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek()?.clone();
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn error(&self, message: &str) -> io::Error {
+        parse_error(self.tokens[self.pos].0, message)
+    }
+
+    fn accept(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), io::Error> {
+        if self.accept(op) { Ok(()) } else { Err(self.error(&format!("expected '{}'", op))) }
+    }
+
+    fn or(&mut self) -> Result<DltFilter, io::Error> {
+        let mut result = vec![self.and()?];
+        while self.accept("||") {
+            result.push(self.and()?);
+        }
+        Ok(if result.len() == 1 { result.remove(0) } else { DltFilter::Or(result) })
+    }
+
+    fn and(&mut self) -> Result<DltFilter, io::Error> {
+        let mut result = vec![self.unary()?];
+        while self.accept("&&") {
+            result.push(self.unary()?);
+        }
+        Ok(if result.len() == 1 { result.remove(0) } else { DltFilter::and(result) })
+    }
+
+    fn unary(&mut self) -> Result<DltFilter, io::Error> {
+        if self.accept("!") {
+            return Ok(DltFilter::Not(Box::new(self.unary()?)));
+        }
+        if self.accept("(") {
+            let result = self.or()?;
+            self.expect(")")?;
+            return Ok(result);
+        }
+        self.clause()
+    }
+
+    fn clause(&mut self) -> Result<DltFilter, io::Error> {
+        let field = match self.next() {
+            Some(Token::Ident(field)) => field,
+            _ => { self.pos = self.pos.saturating_sub(1); return Err(self.error("expected a field")) },
+        };
+        let op = match self.next() {
+            Some(Token::Op(op)) => op.to_string(),
+            Some(Token::Ident(op)) if op == "in" || op == "contains" => op,
+            _ => { self.pos -= 1; return Err(self.error("expected an operator")) },
+        };
+
+        match (field.as_str(), op.as_str()) {
+            ("ecu" | "apid" | "ctid", "==" | "!=" | "in") => {
+                let field = match field.as_str() {
+                    "ecu" => DltIdField::Ecu,
+                    "apid" => DltIdField::Apid,
+                    _ => DltIdField::Ctid,
+                };
+                let ids = if op == "in" { self.list()? } else { vec![self.value()?] };
+                let ids = ids.iter().map(|id| match id {
+                    Token::Str(s) | Token::Ident(s) => id_from_str(s),
+                    _ => Err(self.error("expected an ID")),
+                }).collect::<Result<Vec<_>, io::Error>>()?;
+                Ok(negate(op == "!=", DltFilter::Id(field, ids)))
+            },
+            ("level", "==" | "!=" | "<" | "<=" | ">" | ">=") => {
+                let level = match self.value()? {
+                    Token::Ident(name) => level_from_str(&name),
+                    Token::Number(num) => num.parse().ok().filter(|l| (1..=6).contains(l)),
+                    _ => None,
+                }.ok_or_else(|| self.error("expected a log level"))?;
+                Ok(DltFilter::Level(cmp_from_str(&op), level))
+            },
+            ("type", "==" | "!=") => {
+                let typ = match self.value()? {
+                    Token::Ident(name) => ["log", "app_trace", "nw_trace", "control"].iter().position(|t| *t == name),
+                    _ => None,
+                }.ok_or_else(|| self.error("expected a message type"))?;
+                Ok(negate(op == "!=", DltFilter::MessageType(typ as u8)))
+            },
+            ("verbose", "==" | "!=") => {
+                let verbose = match self.value()? {
+                    Token::Ident(v) if v == "true" => true,
+                    Token::Ident(v) if v == "false" => false,
+                    _ => return Err(self.error("expected true or false")),
+                };
+                Ok(negate(op == "!=", DltFilter::Verbose(verbose)))
+            },
+            ("payload", "=~" | "!~" | "contains") => {
+                let pattern = match (self.value()?, op.as_str()) {
+                    (Token::Regex(re), "=~" | "!~") => re,
+                    (Token::Str(s), _) => regex::escape(&s),
+                    _ => return Err(self.error("expected a regex or a string")),
+                };
+                let regex = Regex::new(&pattern).map_err(|e| self.error(&e.to_string()))?;
+                Ok(negate(op == "!~", DltFilter::Payload(regex)))
+            },
+            ("time", "in") => {
+                let range = self.list()?;
+                if range.len() != 2 {
+                    return Err(self.error("expected [from, to]"));
+                }
+                match (self.time(&range[0])?, self.time(&range[1])?) {
+                    ((false, from), (false, to)) => Ok(DltFilter::StorageTime(from, to)),
+                    ((true, from), (true, to)) => Ok(DltFilter::TimeOfDay(from, to)),
+                    _ => Err(self.error("time range mixes time of day and seconds since 1.1.1970")),
+                }
+            },
+            ("time", "<" | "<=" | ">" | ">=") => {
+                let value = self.value()?;
+                let (of_day, time) = self.time(&value)?;
+                let (min, max) = if of_day { (0, USECS_PER_DAY - 1) } else { (i64::MIN, i64::MAX) };
+                let (from, to) = match op.as_str() {
+                    "<" => (min, time - 1),
+                    "<=" => (min, time),
+                    ">" => (time + 1, max),
+                    _ => (time, max),
+                };
+                Ok(if of_day { DltFilter::TimeOfDay(from, to) } else { DltFilter::StorageTime(from, to) })
+            },
+            _ => {
+                self.pos -= 1;
+                Err(self.error(&format!("operator '{}' is not supported for field '{}'", op, field)))
+            },
+        }
+    }
+
+    fn value(&mut self) -> Result<Token, io::Error> {
+        match self.next() {
+            Some(Token::Op(_)) | None => { self.pos = self.pos.saturating_sub(1); Err(self.error("expected a value")) },
+            Some(token) => Ok(token),
+        }
+    }
+
+    fn list(&mut self) -> Result<Vec<Token>, io::Error> {
+        self.expect("[")?;
+        let mut result = vec![self.value()?];
+        while self.accept(",") {
+            result.push(self.value()?);
+        }
+        self.expect("]")?;
+        Ok(result)
+    }
+
+    /// Returns ``(is time of day, microseconds)``.
+    fn time(&self, token: &Token) -> Result<(bool, i64), io::Error> {
+        let Token::Number(time) = token else {
+            return Err(self.error("expected a time"));
+        };
+        let invalid = || self.error("invalid time");
+        let parts: Vec<&str> = time.split(':').collect();
+        let (secs, fraction) = parts[parts.len() - 1].split_once('.').unwrap_or((parts[parts.len() - 1], ""));
+        let fraction: i64 = format!("{:0<6}", &fraction[..fraction.len().min(6)]).parse().map_err(|_| invalid())?;
+        match parts.len() {
+            1 => {
+                let secs: i64 = secs.parse().map_err(|_| invalid())?;
+                Ok((false, secs * 1_000_000 + if secs < 0 { -fraction } else { fraction }))
+            },
+            2 | 3 => {
+                let hours: i64 = parts[0].parse().map_err(|_| invalid())?;
+                let minutes: i64 = parts[1].parse().map_err(|_| invalid())?;
+                let secs: i64 = if parts.len() == 3 { secs.parse().map_err(|_| invalid())? } else { 0 };
+                if hours > 23 || minutes > 59 || secs > 59 || (parts.len() == 2 && fraction > 0) {
+                    return Err(invalid());
+                }
+                Ok((true, ((hours * 60 + minutes) * 60 + secs) * 1_000_000 + fraction))
+            },
+            _ => Err(invalid()),
+        }
+    }
+}
+
+fn negate(negate: bool, filter: DltFilter) -> DltFilter {
+    if negate { DltFilter::Not(Box::new(filter)) } else { filter }
+}
+
+fn cmp_from_str(op: &str) -> DltCmp {
+    match op {
+        "==" => DltCmp::Eq,
+        "!=" => DltCmp::Ne,
+        "<" => DltCmp::Lt,
+        "<=" => DltCmp::Le,
+        ">" => DltCmp::Gt,
+        _ => DltCmp::Ge,
+    }
+}
+
+/// Converts a log level name into its value, ``fatal`` is ``1``, ``verbose`` is ``6``.
+pub fn level_from_str(name: &str) -> Option<u8> {
+    match name {
+        "fatal" => Some(1),
+        "error" => Some(2),
+        "warn" => Some(3),
+        "info" => Some(4),
+        "debug" => Some(5),
+        "verbose" => Some(6),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{dlt_v1::{dltit, DltLogMessageTypeInfo}, dltbuffer::DltBuffer, DltGrepIterator};
+
+    use super::*;
+
+    fn count(bytes: &[u8], expr: &str) -> usize {
+        let filter = DltFilter::parse(expr).expect("valid filter");
+        dltit(bytes).filter(filter.predicate()).count()
+    }
+
+    fn level(e: &DltStorageEntry) -> Option<u8> {
+        match e.dlt.extended_header()?.msin.message_type()? {
+            DltMessageType::DltTypeLog(info) => Some(info as u8),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn filter_ids() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex004.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        let num_a004 = count(bytes, r#"apid == "A004""#);
+        assert_eq!(42571, num_a004);
+        assert_eq!(52451, count(bytes, r#"ecu == "E001""#));
+        assert_eq!(0, count(bytes, r#"ecu != E001"#));
+
+        // messages without extended header do not match any APID
+        assert_eq!(52451 - num_a004, count(bytes, r#"apid != "A004""#));
+        assert_eq!(52451 - num_a004, count(bytes, r#"!(apid == "A004" && type == log)"#));
+
+        let other = dltit(bytes).filter_map(|e| e.dlt.extended_header().map(|ext| ext.apid)).find(|a| *a != *b"A004").unwrap();
+        let expected = dltit(bytes).filter(|e| e.dlt.extended_header().is_some_and(|ext| ext.apid == *b"A004" || ext.apid == other)).count();
+        let other = String::from_utf8_lossy(&other).trim_end_matches('\0').to_string();
+        assert_eq!(expected, count(bytes, &format!(r#"apid in ["A004", "{}"]"#, other)));
+    }
+
+    #[test]
+    fn filter_level_and_payload() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex003.dlt").expect("test file");
+        let bytes = buf.as_slice();
+
+        let expected = dltit(bytes).filter(|e| level(e).is_some_and(|l| l <= DltLogMessageTypeInfo::DltLogWarn as u8)).count();
+        assert_eq!(expected, count(bytes, "level <= warn"));
+        let expected = dltit(bytes).filter(|e| level(e) == Some(4)).count();
+        assert_eq!(expected, count(bytes, "level == info"));
+        assert_eq!(expected, count(bytes, "level == 4"));
+
+        let grep = DltGrepIterator::new("Counter", bytes, 0).count();
+        assert_eq!(grep, count(bytes, "payload =~ /Count(er)?/ && verbose == true"));
+        assert_eq!(grep, count(bytes, r#"payload contains "Counter""#));
+        assert_eq!(8045 - grep, count(bytes, "payload !~ /Counter/"));
+        assert_eq!(grep, count(bytes, r#"ecu == "ECU" && apid == "SER" && payload =~ /Counter/"#));
+    }
+
+    #[test]
+    fn filter_payload_last() {
+        // clauses that access the payload, also within ``Or`` and ``Not``, are evaluated after the header clauses
+        let filter = DltFilter::parse(r#"payload =~ /x/ && !(payload contains "y") && (ctid == "C" || payload =~ /z/) && apid == "A" && level <= warn"#)
+            .expect("valid filter");
+        match filter {
+            DltFilter::And(clauses) => {
+                assert!(matches!(&clauses[0], DltFilter::Id(DltIdField::Apid, _)));
+                assert!(matches!(&clauses[1], DltFilter::Level(DltCmp::Le, 3)));
+                assert!(matches!(&clauses[2], DltFilter::Payload(_)));
+                assert!(matches!(&clauses[3], DltFilter::Not(_)));
+                assert!(matches!(&clauses[4], DltFilter::Or(_)));
+            },
+            _ => panic!("expected a conjunction"),
+        }
+
+        let buf = DltBuffer::mmap_file("../test/lc_ex003.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        assert_eq!(count(bytes, r#"apid == "SER" && payload =~ /Counter/"#), count(bytes, r#"payload =~ /Counter/ && apid == "SER""#));
+    }
+
+    #[test]
+    fn filter_time() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex002.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        let times: Vec<i64> = dltit(bytes).map(|e| e.storage_header.timestamp_us()).collect();
+        let (from, to) = (times[1000], times[2000]);
+
+        let expected = times.iter().filter(|t| (from..=to).contains(*t)).count();
+        let secs = |t: i64| format!("{}.{:06}", t / 1_000_000, t % 1_000_000);
+        assert_eq!(expected, count(bytes, &format!("time in [{}, {}]", secs(from), secs(to))));
+        assert_eq!(expected, count(bytes, &format!("time >= {} && time <= {}", secs(from), secs(to))));
+
+        let of_day = |t: i64| {
+            let t = t.rem_euclid(USECS_PER_DAY);
+            format!("{:02}:{:02}:{:02}.{:06}", t / 3_600_000_000, t / 60_000_000 % 60, t / 1_000_000 % 60, t % 1_000_000)
+        };
+        let expected = times.iter().filter(|t| (from.rem_euclid(USECS_PER_DAY)..=to.rem_euclid(USECS_PER_DAY)).contains(&t.rem_euclid(USECS_PER_DAY))).count();
+        assert_eq!(expected, count(bytes, &format!("time in [{}, {}]", of_day(from), of_day(to))));
+    }
+
+    /// Selects the filters with the given names from ``test/filters.dlf``, which was exported by dlt-viewer.
+    fn dlf(names: &[&str]) -> String {
+        let xml = std::fs::read_to_string("../test/filters.dlf").expect("test file");
+        let filters: String = xml.split("    <filter>\n").skip(1)
+            .filter_map(|filter| filter.split("    </filter>\n").next())
+            .filter(|filter| names.iter().any(|name| filter.contains(&format!("<name>{}</name>", name))))
+            .map(|filter| format!("    <filter>\n{}    </filter>\n", filter))
+            .collect();
+        format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<dltfilter>\n{}</dltfilter>\n", filters)
+    }
+
+    #[test]
+    fn filter_dlf() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex004.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        let dlf_count = |xml: &str| {
+            let filter = DltFilter::from_dlf(xml).expect("valid dlf");
+            dltit(bytes).filter(filter.predicate()).count()
+        };
+
+        // no enabled positive filter selects all messages
+        assert_eq!(52451, dlf_count(&dlf(&[])));
+        assert_eq!(52451, dlf_count(&dlf(&["disabled"])));
+
+        assert_eq!(42571, dlf_count(&dlf(&["A004"])));
+        assert_eq!(count(bytes, r#"apid in ["A004", "A005"]"#), dlf_count(&dlf(&["A00[45]"])));
+        // level criteria do not exclude messages of other types
+        assert_eq!(count(bytes, r#"apid == "A004" || type != log || level <= warn"#), dlf_count(&dlf(&["A004", "warnings"])));
+
+        // negative filters exclude messages from the result of the positive filters, a negative level filter also
+        // excludes messages of other types
+        assert_eq!(count(bytes, r#"apid == "A004" && !(type != log || level >= info)"#), dlf_count(&dlf(&["A004", "no info"])));
+        assert_eq!(count(bytes, "!(type != log || level >= info)"), dlf_count(&dlf(&["no info"])));
+
+        // marker filters do not change the selection
+        assert_eq!(42571, dlf_count(&dlf(&["A004", "marker"])));
+    }
+
+    #[test]
+    fn filter_dlf_payload() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex003.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        let dlf_count = |xml: &str| {
+            let filter = DltFilter::from_dlf(xml).expect("valid dlf");
+            dltit(bytes).filter(filter.predicate()).count()
+        };
+        let grep = DltGrepIterator::new("Counter", bytes, 0).count();
+
+        assert_eq!(grep, dlf_count(&dlf(&["Counter"])));
+        // the payload is matched after the header criteria of the filter
+        let xml = dlf(&["Counter"]).replace("<enableLogLevelMax>0", "<enableLogLevelMax>1");
+        let DltFilter::And(filters) = DltFilter::from_dlf(&xml).expect("valid dlf") else { panic!("expected a conjunction") };
+        let [DltFilter::Or(positive)] = &filters[..] else { panic!("expected the positive filters") };
+        let [DltFilter::And(clauses)] = &positive[..] else { panic!("expected the clauses of the filter") };
+        assert!(clauses.len() > 1);
+        assert!(matches!(clauses.last(), Some(DltFilter::Payload(_))));
+        assert_eq!(count(bytes, r#"(type != log || level <= verbose) && payload =~ /Counter/"#), dlf_count(&xml));
+        assert_eq!(0, dlf_count(&dlf(&["counter"])));
+        assert_eq!(grep, dlf_count(&dlf(&["counter ignore case"])));
+        assert_eq!(grep, dlf_count(&dlf(&["Coun.er"])));
+        assert_eq!(8045 - grep, dlf_count(&dlf(&["no Counter"])));
+        // without regex, the payload text is matched literally
+        assert_eq!(0, dlf_count(&dlf(&["Coun.er"]).replace("<enableregex_Payload>1", "<enableregex_Payload>0")));
+        // older dlt-viewer versions write a single regex flag for all fields
+        assert_eq!(grep, dlf_count(&dlf(&["Coun.er"]).replace("enableregex_Payload", "enableregex")));
+
+        assert!(DltFilter::from_dlf("<dltfilter><filter>").is_err());
+        assert!(DltFilter::from_dlf(&dlf(&["header"])).is_err());
+        assert!(DltFilter::from_dlf(&dlf(&["warnings"]).replace("<logLevelMax>3", "<logLevelMax>")).is_err());
+        assert!(DltFilter::open_dlf("../test/filters.dlf").is_err());
+    }
+
+    #[test]
+    fn filter_parse() {
+        let filter = DltFilter::parse(r#"ecu == "ECU1" && apid in ["NAV","HMI"] && level <= warn && payload =~ /timeout/ && time in [10:02:00, 10:05:00]"#).expect("valid filter");
+        match filter {
+            DltFilter::And(clauses) => {
+                assert_eq!(5, clauses.len());
+                assert!(matches!(&clauses[1], DltFilter::Id(DltIdField::Apid, ids) if *ids == vec![*b"NAV\0", *b"HMI\0"]));
+                assert!(matches!(&clauses[2], DltFilter::Level(DltCmp::Le, 3)));
+                assert!(matches!(&clauses[3], DltFilter::TimeOfDay(from, to) if *from == 36_120_000_000 && *to == 36_300_000_000));
+                assert!(matches!(&clauses[4], DltFilter::Payload(_)));
+            },
+            _ => panic!("expected a conjunction"),
+        }
+        assert!(matches!(DltFilter::parse(r#"(apid == "A" || !(ctid == "B")) && payload =~ /a\/b\d/"#), Ok(DltFilter::And(_))));
+
+        for invalid in ["", "ecu", "ecu ==", "apid == \"TOOLONG\"", "level <= loud", "payload =~ /(/", "time in [10:00, 1700000000]",
+                        "payload == \"x\"", "apid == \"A\" &&", "(apid == \"A\"", "apid == \"A\" apid", "time in [25:00, 26:00]", "ecu == \"E\" # x"] {
+            assert!(DltFilter::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use std::{fs::{self, File, Metadata}, io::{self, Read, Seek, SeekFrom}, ops::ControlFlow, path::PathBuf, thread, time::Duration};
+
+use crate::{dlt_v1::{dltit_offset, DltStorageEntry}, dltbuffer::complete_prefix};
+
+enum FileState {
+    Unchanged,
+    Rotated,
+    Truncated,
+}
+
+///
+/// ``DltFollower``: follows a DLT file that is still being written, like ``tail -f``.
+///
+/// Growth of the file is detected by polling. A partially written last message is kept
+/// until it is complete and is not treated as corrupted data. If the file is rotated by rename,
+/// the remaining messages of the old file are returned before the new file is followed from its start.
+///
+pub struct DltFollower {
+    filename: PathBuf,
+    file: File,
+    id: (u64, u64),
+    window: Vec<u8>,
+    complete: usize,
+    position: usize,
+    interval: Duration,
+}
+
+impl DltFollower {
+    pub fn open (filename: &str) -> Result<Self,io::Error> {
+        let file = File::open(filename)?;
+        let id = file_id(&file.metadata()?);
+        Ok(Self {
+            filename: PathBuf::from(filename),
+            file,
+            id,
+            window: vec![],
+            complete: 0,
+            position: 0,
+            interval: Duration::from_millis(100),
+        })
+    }
+
+    /// Sets the interval used by ``DltFollower::follow`` to poll for new data.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Offset of ``DltFollower::as_slice`` within the currently followed file.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Complete DLT messages found by the last ``DltFollower::poll`` call.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.window[..self.complete]
+    }
+
+    /// Reads data appended since the last call and returns the number of bytes of complete DLT messages,
+    /// which are accessible with ``DltFollower::as_slice`` until the next call.
+    pub fn poll(&mut self) -> Result<usize, io::Error> {
+        self.consume(self.complete);
+        self.complete = 0;
+
+        loop {
+            self.file.read_to_end(&mut self.window)?;
+
+            let (end, skip) = complete_prefix(&self.window);
+
+            if let Some(skip) = skip {
+                // the data at ``end`` cannot become a valid message anymore, skip it
+                if end == 0 {
+                    self.consume(skip);
+                    continue;
+                }
+                self.complete = end;
+                return Ok(end);
+            }
+            if end > 0 {
+                self.complete = end;
+                return Ok(end);
+            }
+
+            match self.state()? {
+                FileState::Unchanged => return Ok(0),
+                FileState::Rotated => {
+                    // the old file is read completely, a pending incomplete message will never be finished
+                    self.file = File::open(&self.filename)?;
+                    self.id = file_id(&self.file.metadata()?);
+                    self.window.clear();
+                    self.position = 0;
+                },
+                FileState::Truncated => {
+                    self.file.seek(SeekFrom::Start(0))?;
+                    self.window.clear();
+                    self.position = 0;
+                },
+            }
+        }
+    }
+
+    /// Polls the file until ``f`` returns ``ControlFlow::Break``, calls ``f`` with the offset within the followed file
+    /// for each new DLT message.
+    pub fn follow<F>(&mut self, mut f: F) -> Result<(), io::Error>
+    where
+        F: FnMut(usize, DltStorageEntry) -> ControlFlow<()>
+    {
+        loop {
+            if self.poll()? == 0 {
+                thread::sleep(self.interval);
+                continue;
+            }
+            let position = self.position();
+            for (offset, entry) in dltit_offset(self.as_slice()) {
+                if f(position + offset, entry).is_break() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn consume(&mut self, num: usize) {
+        self.window.drain(..num);
+        self.position += num;
+    }
+
+    fn state(&self) -> Result<FileState, io::Error> {
+        let meta = match fs::metadata(&self.filename) {
+            Ok(meta) => meta,
+            // the file was renamed, but the new file was not yet created
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(FileState::Unchanged),
+            Err(e) => return Err(e),
+        };
+        if file_id(&meta) != self.id {
+            Ok(FileState::Rotated)
+        } else if (meta.len() as usize) < self.position + self.window.len() {
+            Ok(FileState::Truncated)
+        } else {
+            Ok(FileState::Unchanged)
+        }
+    }
+}
+
+#[cfg(unix)]
+fn file_id(meta: &Metadata) -> (u64, u64) {
+    use std::os::unix::fs::MetadataExt;
+    (meta.dev(), meta.ino())
+}
+
+#[cfg(not(unix))]
+fn file_id(_meta: &Metadata) -> (u64, u64) {
+    (0, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::OpenOptions, io::Write};
+
+    use crate::{dlt_v1::dltit, dltbuffer::DltBuffer};
+
+    use super::*;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("rapiddlt_{}_{}.dlt", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn append(path: &PathBuf, bytes: &[u8]) {
+        let mut file = OpenOptions::new().create(true).append(true).open(path).unwrap();
+        file.write_all(bytes).unwrap();
+    }
+
+    #[test]
+    fn follow_partial_message() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex002.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        let path = temp_file("partial");
+
+        // split in the middle of a message
+        let split = bytes.len() / 2 + 7;
+        append(&path, &bytes[..split]);
+
+        let mut follower = DltFollower::open(path.to_str().unwrap()).unwrap();
+        let first = follower.poll().unwrap();
+        let num_first = dltit(follower.as_slice()).count();
+        assert!(first < split);
+        assert_eq!(0, follower.poll().unwrap());
+
+        append(&path, &bytes[split..]);
+        assert_eq!(bytes.len() - first, follower.poll().unwrap());
+        assert_eq!(first, follower.position());
+        let num_second = dltit(follower.as_slice()).count();
+
+        assert_eq!(11696, num_first + num_second);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn follow_rotation() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex003.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        let path = temp_file("rotation");
+        let rotated = path.with_extension("dlt.1");
+
+        append(&path, &bytes[..1000]);
+        let mut follower = DltFollower::open(path.to_str().unwrap()).unwrap();
+        follower.poll().unwrap();
+        let mut count = dltit(follower.as_slice()).count();
+
+        append(&path, &bytes[1000..]);
+        fs::rename(&path, &rotated).unwrap();
+        append(&path, bytes);
+
+        let mut entries = vec![];
+        follower.follow(|offset, e| {
+            entries.push((offset, e.dlt.header.length()));
+            if count + entries.len() == 2 * 8045 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        }).unwrap();
+        count += entries.len();
+
+        assert_eq!(2 * 8045, count);
+        // the last message was read from the new file
+        assert_eq!(bytes.len(), entries.last().unwrap().0 + entries.last().unwrap().1 + 16);
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&rotated).unwrap();
+    }
+
+    #[test]
+    fn follow_corrupted() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex004.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        let path = temp_file("corrupted");
+
+        let (split, _) = dltit_offset(bytes).find(|(offset, _)| *offset >= 100000).unwrap();
+        append(&path, &bytes[..split]);
+        // a marker without valid DLT message, followed by more data than any DLT message can hold
+        append(&path, &[b'D',b'L',b'T', 0x1, 0xFF, 0xFF]);
+        append(&path, &vec![0u8; 70000]);
+        append(&path, &bytes[split..]);
+
+        let mut follower = DltFollower::open(path.to_str().unwrap()).unwrap();
+        let mut count = 0;
+        while follower.poll().unwrap() > 0 {
+            count += dltit(follower.as_slice()).count();
+        }
+        assert_eq!(52451, count);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,289 @@
+use std::{iter::Peekable, ops::Range};
+
+use matchit::{searchable::grepit::ScopedGrepIterator, FromBytesReadableTrait, WithOffset};
+
+use crate::{dlt_v1::DltStorageEntry, dltcursor::DltCursor, dltverbose::{arguments, DltValue}};
+
+/// ``ScopedGrepIterator`` that only accepts matches within the payload, see ``grep_payload``.
+pub type DltPayloadGrepIterator<'bytes> = ScopedGrepIterator<'bytes, DltStorageEntry<'bytes>, fn(&DltStorageEntry<'bytes>) -> Option<Range<usize>>>;
+
+/// Range of the payload of ``entry``, relative to the start of its storage header.
+pub fn payload_scope(entry: &DltStorageEntry) -> Option<Range<usize>> {
+    let payload = entry.dlt.payload()?;
+    Some(entry.len() - payload.len()..entry.len())
+}
+
+///
+/// ``grep_payload``: returns the messages of ``bytes`` with a match of ``pattern`` in the payload.
+///
+/// Unlike ``DltGrepIterator``, matches in the storage, standard or extended header, e.g., of the ECU ID, are ignored.
+/// The regex is still searched over the whole slice.
+///
+pub fn grep_payload<'bytes>(pattern: &str, bytes: &'bytes [u8]) -> DltPayloadGrepIterator<'bytes> {
+    ScopedGrepIterator::new(pattern, bytes, 0, payload_scope)
+}
+
+///
+/// ``grep_argument``: returns the verbose messages of ``bytes`` with a match of ``pattern`` in the string argument
+/// with number ``index``. Messages whose argument ``index`` is no string are ignored.
+///
+pub fn grep_argument<'bytes>(pattern: &str, bytes: &'bytes [u8], index: usize) -> impl Iterator<Item = WithOffset<DltStorageEntry<'bytes>>> {
+    ScopedGrepIterator::new(pattern, bytes, 0, move |entry: &DltStorageEntry| {
+        let payload = payload_scope(entry)?;
+        let arg = arguments(entry)?.nth(index)?;
+        match arg.value {
+            DltValue::Str(_) => Some(payload.start + arg.data.start..payload.start + arg.data.end),
+            _ => None,
+        }
+    })
+}
+
+/// Consecutive messages around one or more matches, see ``DltContextIterator``.
+#[derive(Debug)]
+pub struct DltContextGroup<'bytes> {
+    pub messages: Vec<WithOffset<DltStorageEntry<'bytes>>>,
+    /// Indices of the matching messages in ``messages``.
+    pub matches: Vec<usize>,
+}
+
+///
+/// ``DltContextIterator``: adds the ``before`` messages before and the ``after`` messages after each match of
+/// ``hits``, like ``grep -B/-A``, e.g., for the matches of a ``DltGrepIterator``.
+///
+/// Matches whose context windows overlap or touch are returned in the same ``DltContextGroup``, so that each message
+/// is returned at most once. ``hits`` needs to return matches ordered by offset. A match within the payload of
+/// another message, e.g., a nested message, belongs to that message. Stepping backwards uses ``DltCursor::prev``,
+/// which rejects DLT markers within payloads.
+///
+pub struct DltContextIterator<'bytes, I: Iterator> {
+    hits: Peekable<I>,
+    cursor: DltCursor<'bytes>,
+    before: usize,
+    after: usize,
+    /// End of the last group.
+    end: usize,
+}
+
+impl<'bytes, I> DltContextIterator<'bytes, I>
+where
+    I: Iterator<Item = WithOffset<DltStorageEntry<'bytes>>>
+{
+    pub fn new(hits: I, bytes: &'bytes [u8], before: usize, after: usize) -> Self {
+        Self { hits: hits.peekable(), cursor: DltCursor::new(bytes), before, after, end: 0 }
+    }
+}
+
+impl<'bytes, I> Iterator for DltContextIterator<'bytes, I>
+where
+    I: Iterator<Item = WithOffset<DltStorageEntry<'bytes>>>
+{
+    type Item = DltContextGroup<'bytes>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (first, _) = *self.hits.peek()?;
+        // a hit within the payload of another message belongs to that message
+        let start = self.cursor.seek_offset(first).max(self.end);
+
+        self.cursor.set_position(start);
+        let mut messages: Vec<WithOffset<DltStorageEntry>> = (0..self.before)
+            .map_while(|_| self.cursor.prev().filter(|(offset, _)| *offset >= self.end))
+            .collect();
+        messages.reverse();
+
+        // read forward until the next match is too far away to share the context
+        let mut matches = vec![];
+        self.cursor.set_position(start);
+        while let Some((offset, entry)) = self.cursor.next() {
+            let mut is_match = false;
+            while self.hits.next_if(|(hit, _)| *hit < offset + entry.len()).is_some() {
+                is_match = true;
+            }
+            messages.push((offset, entry));
+            if is_match {
+                matches.push(messages.len() - 1);
+            } else if matches.is_empty() || messages.len() - 1 - matches[matches.len() - 1] > self.after + self.before {
+                break;
+            }
+        }
+        // no valid message at or after the hit
+        if matches.is_empty() {
+            return None
+        }
+
+        messages.truncate(matches[matches.len() - 1] + self.after + 1);
+        self.end = messages.last().map(|(offset, entry)| offset + entry.len()).unwrap_or(self.end);
+        Some(DltContextGroup { messages, matches })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::bytes::Regex;
+
+    use matchit::{partition_from, searchable::grepit::{CaptureValue, MultiGrepPatterns}};
+
+    use crate::{dlt_v1::{dltit, dltit_offset}, dltbuffer::DltBuffer, dlttest::{string, TestMessage}, DltCaptureIterator, DltGrepIterator, DltMultiGrepIterator};
+
+    use super::*;
+
+    fn message(payload: &[u8]) -> Vec<u8> {
+        TestMessage { extended: Some((2, *b"APP1", *b"CTX1")), ..Default::default() }.bytes(payload)
+    }
+
+    #[test]
+    fn grep_payload_only() {
+        let bytes = [message(&[string("start"), string("APP1 ready")].concat()), message(&[string("APP1"), string("ECU")].concat())].concat();
+
+        assert_eq!(2, DltGrepIterator::new("ECU1", &bytes, 0).count());
+        assert_eq!(0, grep_payload("ECU1", &bytes).count());
+        assert_eq!(0, grep_payload("CTX1", &bytes).count());
+        assert_eq!(2, grep_payload("APP1", &bytes).count());
+        // a match spanning header and payload is not in the payload
+        assert_eq!(0, grep_payload("CTX1.*start", &bytes).count());
+
+        let offsets: Vec<usize> = grep_argument("APP1", &bytes, 1).map(|(offset, _)| offset).collect();
+        assert_eq!(vec![0], offsets);
+        assert_eq!(1, grep_argument("APP1", &bytes, 0).count());
+        assert_eq!(0, grep_argument("APP1", &bytes, 2).count());
+
+        // a greedy match running past the argument or the payload is searched again within it
+        let bytes = [message(&[string("Hello world"), string("and more")].concat()), message(&string("Hello ECU2")), message(&string("ok"))].concat();
+        assert_eq!(1, grep_argument("H.*d", &bytes, 0).count());
+        assert_eq!(0, grep_argument("H.*d", &bytes, 1).count());
+        assert_eq!(1, grep_argument(r"\w+ \w+", &bytes, 1).count());
+        let offsets: Vec<usize> = grep_payload("H[^\x00]*[dU].*", &bytes).map(|(offset, _)| offset).collect();
+        assert_eq!(2, offsets.len());
+    }
+
+    #[test]
+    fn grep_payload_trace() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex003.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        for pattern in ["Counter", "ECU", "SER"] {
+            let regex = Regex::new(pattern).unwrap();
+            let expected: Vec<usize> = dltit_offset(bytes)
+                .filter(|(_, e)| e.dlt.payload().is_some_and(|p| regex.is_match(p)))
+                .map(|(offset, _)| offset)
+                .collect();
+            let found: Vec<usize> = grep_payload(pattern, bytes).map(|(offset, _)| offset).collect();
+            assert_eq!(expected, found, "{}", pattern);
+        }
+        assert_eq!(1323, grep_payload("Counter", bytes).count());
+
+        let regex = Regex::new("Counter").unwrap();
+        let expected = dltit(bytes)
+            .filter(|e| arguments(e).and_then(|mut args| args.next()).is_some_and(|a| matches!(a.value, DltValue::Str(s) if regex.is_match(s))))
+            .count();
+        assert_eq!(expected, grep_argument("Counter", bytes, 0).count());
+    }
+
+    #[test]
+    fn multigrep_trace() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex003.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        let patterns = ["Counter", "ECU", r"SER\x00", "no such pattern", r"Counter \d*5\b"];
+        let regexes: Vec<Regex> = patterns.iter().map(|p| Regex::new(p).unwrap()).collect();
+
+        let expected: Vec<(usize, Vec<usize>)> = dltit_offset(bytes)
+            .map(|(offset, e)| (offset, (0..patterns.len()).filter(|i| regexes[*i].is_match(&bytes[offset..offset + e.len()])).collect::<Vec<_>>()))
+            .filter(|(_, ids)| !ids.is_empty())
+            .collect();
+        let patterns = MultiGrepPatterns::new(&patterns).unwrap();
+        let found: Vec<(usize, Vec<usize>)> = DltMultiGrepIterator::new(&patterns, bytes, 0).map(|(offset, (_, ids))| (offset, ids)).collect();
+        assert_eq!(expected, found);
+        assert_eq!(1323, found.iter().filter(|(_, ids)| ids.contains(&0)).count());
+
+        // the same result for partitions searched in parallel
+        let partitions = partition_from::<DltStorageEntry>(bytes, 4);
+        let counts = std::thread::scope(|s| {
+            let handles: Vec<_> = partitions.iter()
+                .map(|slice| s.spawn(|| DltMultiGrepIterator::new(&patterns, slice, 0).count()))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum::<usize>()
+        });
+        assert_eq!(found.len(), counts);
+    }
+
+    #[test]
+    fn grep_context() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex003.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        let offsets: Vec<usize> = dltit_offset(bytes).map(|(offset, _)| offset).collect();
+        let hits: Vec<usize> = DltGrepIterator::new("Counter", bytes, 0)
+            .map(|(offset, _)| offsets.binary_search(&offset).unwrap())
+            .collect();
+
+        for (before, after) in [(0, 0), (2, 3), (20, 20), (5, 0)] {
+            let mut expected: Vec<Vec<usize>> = vec![];
+            for hit in hits.iter() {
+                let window = hit.saturating_sub(before)..=(hit + after).min(offsets.len() - 1);
+                match expected.last_mut() {
+                    Some(group) if *group.last().unwrap() + 1 >= *window.start() => group.extend((group.last().unwrap() + 1)..=*window.end()),
+                    _ => expected.push(window.collect()),
+                }
+            }
+
+            let groups: Vec<DltContextGroup> = DltContextIterator::new(DltGrepIterator::new("Counter", bytes, 0), bytes, before, after).collect();
+            let found: Vec<Vec<usize>> = groups.iter()
+                .map(|g| g.messages.iter().map(|(offset, _)| offsets.binary_search(offset).unwrap()).collect())
+                .collect();
+            assert_eq!(expected, found, "-B {} -A {}", before, after);
+
+            let matches: Vec<usize> = groups.iter().flat_map(|g| g.matches.iter().map(|i| offsets.binary_search(&g.messages[*i].0).unwrap())).collect();
+            assert_eq!(hits, matches);
+        }
+    }
+
+    #[test]
+    fn grep_context_nested() {
+        let inner = message(&string("inner"));
+        let outer = message(&[string("outer"), inner.clone()].concat());
+        let first = message(&string("first"));
+        let last = message(&string("last"));
+        let bytes = [first.as_slice(), &outer, &last, &inner].concat();
+
+        // the nested message is returned as the containing message
+        let groups: Vec<Vec<usize>> = DltContextIterator::new(DltGrepIterator::new("inner", &bytes, 0), &bytes, 0, 0)
+            .map(|g| g.messages.iter().map(|(offset, _)| *offset).collect())
+            .collect();
+        assert_eq!(vec![vec![first.len()], vec![bytes.len() - inner.len()]], groups);
+
+        let groups: Vec<DltContextGroup> = DltContextIterator::new(DltGrepIterator::new("inner", &bytes, 0), &bytes, 1, 0).collect();
+        assert_eq!(1, groups.len());
+        assert_eq!(4, groups[0].messages.len());
+        assert_eq!(vec![1, 3], groups[0].matches);
+    }
+
+    #[test]
+    fn named_captures() {
+        let bytes = [message(&string("speed=12.5 unit=kmh")), message(&string("speed=7 unit=mph x")), message(&string("no speed"))].concat();
+        let it = DltCaptureIterator::new(r"speed=(?P<speed>[\d.]+) unit=(?P<unit>\w+)(?P<flag> x)?", &bytes, 0);
+        assert_eq!(["speed", "unit", "flag"].as_slice(), it.names());
+
+        let values: Vec<Vec<Option<CaptureValue>>> = it.map(|(_, (_, values))| values).collect();
+        assert_eq!(vec![
+            vec![Some(CaptureValue::Float(12.5)), Some(CaptureValue::Str(b"kmh")), None],
+            vec![Some(CaptureValue::Int(7)), Some(CaptureValue::Str(b"mph")), Some(CaptureValue::Str(b" x"))],
+        ], values);
+
+        // words that str::parse accepts as f64 remain strings
+        for word in [b"inf".as_slice(), b"NaN", b"infinity"] {
+            assert_eq!(CaptureValue::Str(word), CaptureValue::parse(word));
+        }
+        assert_eq!(CaptureValue::Float(-2.5e-3), CaptureValue::parse(b"-2.5e-3"));
+    }
+
+    #[test]
+    fn named_captures_trace() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex003.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        let counters: Vec<i64> = DltCaptureIterator::new(r"Counter: (?P<counter>\d+)", bytes, 0)
+            .filter_map(|(_, (_, values))| match values[0] {
+                Some(CaptureValue::Int(counter)) => Some(counter),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(1323, counters.len());
+        assert_eq!(4323, counters[0]);
+    }
+}
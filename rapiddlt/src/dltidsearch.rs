@@ -0,0 +1,95 @@
+use std::mem;
+
+use matchit::{searchable::fieldit::FieldSearchIterator, WithOffset};
+
+use crate::{dlt_v1::{DltStandardHeader, DltStorageEntry, DltStorageHeader}, dltfilter::DltIdField};
+
+/// Offset of the ECU ID within the storage header.
+const STORAGE_ECU_OFFSET: usize = 12;
+
+/// Offset of ``field`` within ``entry`` including its storage header, or ``None`` if ``entry`` has no such field.
+/// The ECU ID of the standard header takes precedence over the ECU ID of the storage header, like in ``DltFilter``.
+fn field_offset(entry: &DltStorageEntry, field: DltIdField) -> Option<usize> {
+    let header_type = &entry.dlt.header.header_type;
+    let tail = mem::size_of::<DltStorageHeader>() + mem::size_of::<DltStandardHeader>();
+    match field {
+        DltIdField::Ecu if header_type.is_with_ecu_id() => Some(tail),
+        DltIdField::Ecu => Some(STORAGE_ECU_OFFSET),
+        DltIdField::Apid | DltIdField::Ctid => {
+            entry.dlt.extended_header()?;
+            let optional = [header_type.is_with_ecu_id(), header_type.is_with_session_id(), header_type.is_with_timestamp()]
+                .iter()
+                .filter(|x| **x)
+                .count() * 4;
+            // extended header: msin, noar, apid, ctid
+            Some(tail + optional + if field == DltIdField::Apid { 2 } else { 6 })
+        },
+    }
+}
+
+///
+/// ``id_search``: returns the messages of ``bytes`` whose ``field`` equals ``id``, together with their offsets.
+///
+/// Instead of reading each message, ``id`` is searched in the raw bytes. A match is only accepted if it is at the
+/// exact offset of ``field`` within the message that contains it, see ``FieldSearchIterator``. Matches within
+/// payloads and messages within the payload of another message are discarded.
+///
+/// This is much faster than ``dltit(bytes).filter(...)`` for rare IDs, e.g., a single APID in a large trace.
+///
+pub fn id_search(bytes: &[u8], field: DltIdField, id: [u8; 4]) -> impl Iterator<Item = WithOffset<DltStorageEntry<'_>>> {
+    FieldSearchIterator::new(&id, bytes, 0, move |entry: &DltStorageEntry, position| field_offset(entry, field) == Some(position))
+}
+
+#[cfg(test)]
+mod tests {
+    use zerocopy::AsBytes;
+
+    use crate::{dlt_v1::{dltit, dltit_offset, DltExtendedHeader, DltHTyp, DltLogMessageTypeInfo, DltMessageType, MessageType}, dltbuffer::DltBuffer, dltfilter::DltFilter};
+
+    use super::*;
+
+    fn check(bytes: &[u8], field: DltIdField, id: [u8; 4]) -> usize {
+        let filter = DltFilter::Id(field, vec![id]);
+        let expected: Vec<usize> = dltit_offset(bytes).filter(|(_, e)| filter.matches(e)).map(|(offset, _)| offset).collect();
+        let found: Vec<usize> = id_search(bytes, field, id).map(|(offset, _)| offset).collect();
+        assert_eq!(expected, found);
+        found.len()
+    }
+
+    #[test]
+    fn id_search_trace() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex004.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        assert_eq!(42571, check(bytes, DltIdField::Apid, *b"A004"));
+        assert_eq!(52451, check(bytes, DltIdField::Ecu, *b"E001"));
+
+        let ctid = dltit(bytes).filter_map(|e| e.dlt.extended_header().map(|ext| ext.ctid)).nth(100).unwrap();
+        assert!(check(bytes, DltIdField::Ctid, ctid) > 0);
+        assert_eq!(0, check(bytes, DltIdField::Apid, *b"NONE"));
+
+        let buf = DltBuffer::mmap_file("../test/lc_ex003.dlt").expect("test file");
+        assert!(check(buf.as_slice(), DltIdField::Apid, *b"SER\0") > 0);
+        assert!(check(buf.as_slice(), DltIdField::Ecu, *b"ECU\0") > 0);
+    }
+
+    #[test]
+    fn id_search_false_positives() {
+        let message = |apid: &[u8; 4], payload: &[u8]| {
+            let sh = DltStorageHeader::new([b'D',b'L',b'T', 0x1], 1, 0, *b"ECU1");
+            let ext = DltExtendedHeader::new(MessageType::create_message_type(false, DltMessageType::DltTypeLog(DltLogMessageTypeInfo::DltLogInfo)), 0, *apid, *b"CTX1");
+            let length = (mem::size_of::<DltStandardHeader>() + 4 + ext.as_bytes().len() + payload.len()) as u16;
+            let header = DltStandardHeader::new(DltHTyp::new(true, false, true, false, false, 1), 0, length);
+            [sh.as_bytes(), header.as_bytes(), b"ECU1", ext.as_bytes(), payload].concat()
+        };
+        let inner = message(b"APP1", b"inner");
+        let outer = message(b"APP2", &[b"APP1 in payload ".as_slice(), &inner].concat());
+        let last = message(b"APP1", b"CTX1");
+        let bytes = [outer.as_slice(), &last].concat();
+
+        let found: Vec<usize> = id_search(&bytes, DltIdField::Apid, *b"APP1").map(|(offset, _)| offset).collect();
+        assert_eq!(vec![outer.len()], found);
+        let found: Vec<usize> = id_search(&bytes, DltIdField::Ctid, *b"CTX1").map(|(offset, _)| offset).collect();
+        assert_eq!(vec![0, outer.len()], found);
+        assert_eq!(2, id_search(&bytes, DltIdField::Ecu, *b"ECU1").count());
+    }
+}
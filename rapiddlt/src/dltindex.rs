@@ -0,0 +1,304 @@
+use std::{fs::{self, File}, io::{self, BufWriter, Write}, mem, ops::Range, path::PathBuf, thread, time::UNIX_EPOCH};
+
+use matchit::partition_from;
+use zerocopy::{little_endian, AsBytes, FromBytes, FromZeroes};
+
+use crate::{dlt_v1::{dltit, dltit_offset, DltStorageEntry, MessageType}, dltbuffer::DltBuffer, dltcompress::Compression};
+
+const INDEX_MAGIC: [u8; 8] = *b"DLTIDX01";
+
+///
+/// ``DltIndexHeader``: identifies the indexed DLT file by size and modification time.
+///
+#[derive(zerocopy_derive::AsBytes, zerocopy_derive::FromBytes, zerocopy_derive::FromZeroes, Debug)]
+#[repr(C)]
+pub struct DltIndexHeader {
+    pub magic: [u8; 8],
+    pub file_size: little_endian::U64,
+    pub mtime_secs: little_endian::U64,
+    pub mtime_nanos: little_endian::U32,
+    pub stride: little_endian::U32,    // every stride-th message is indexed
+    pub num_messages: little_endian::U64,  // number of messages in the DLT file
+}
+
+///
+/// ``DltIndexRecord``: the header fields of an indexed DLT message. Fields that are not part of the message are zero.
+///
+#[derive(zerocopy_derive::AsBytes, zerocopy_derive::FromBytes, zerocopy_derive::FromZeroes, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct DltIndexRecord {
+    pub ordinal: little_endian::U64,   // number of the message within the DLT file
+    pub offset: little_endian::U64,
+    pub secs: little_endian::U32,      // storage header
+    pub usecs: little_endian::I32,     // storage header
+    pub tmsp: little_endian::U32,      // ECU timestamp in 0.1 milliseconds
+    pub ecu: [u8; 4],
+    pub apid: [u8; 4],
+    pub ctid: [u8; 4],
+    pub msin: MessageType,
+    reserved: [u8; 3],
+}
+
+impl DltIndexRecord {
+    fn new(ordinal: usize, offset: usize, entry: &DltStorageEntry) -> Self {
+        let mut record = Self::new_zeroed();
+        record.ordinal.set(ordinal as u64);
+        record.offset.set(offset as u64);
+        record.secs = entry.storage_header.secs;
+        record.usecs = entry.storage_header.msecs;
+        record.tmsp.set(entry.dlt.timestamp().unwrap_or_default());
+        record.ecu = entry.dlt.ecu_id().map(|id| id.to_be_bytes()).unwrap_or(entry.storage_header.ecu);
+        if let Some(ext) = entry.dlt.extended_header() {
+            record.apid = ext.apid;
+            record.ctid = ext.ctid;
+            record.msin = ext.msin;
+        }
+        record
+    }
+
+    /// Storage time in microseconds since 1.1.1970.
+    pub fn timestamp_us(&self) -> i64 {
+        self.secs.get() as i64 * 1_000_000 + self.usecs.get() as i64
+    }
+}
+
+///
+/// ``DltIndex``: sidecar index of a DLT file, which records the header fields of every ``stride``-th message.
+///
+/// The index is stored as ``<filename>.idx`` next to the DLT file. It consists of a ``DltIndexHeader`` followed by
+/// ``DltIndexRecord``s and is memory-mapped when opened. An index is only valid as long as size and modification time
+/// of the DLT file do not change.
+///
+pub struct DltIndex {
+    buffer: DltBuffer,
+}
+
+impl DltIndex {
+    /// Builds the index of ``bytes`` in parallel on ``num`` partitions.
+    pub fn build(bytes: &[u8], stride: usize, num: usize) -> Self {
+        let stride = stride.max(1);
+        let slices = partition_from::<DltStorageEntry>(bytes, num.max(1));
+
+        // the ordinal of the first message of each partition is needed to select every stride-th message
+        let counts: Vec<usize> = thread::scope(|s| {
+            let handles: Vec<_> = slices.iter().map(|slice| s.spawn(|| dltit(slice).count())).collect();
+            handles.into_iter().map(|h| h.join().expect("index thread panicked")).collect()
+        });
+        let starts: Vec<usize> = counts.iter()
+            .scan(0, |acc, count| { let start = *acc; *acc += count; Some(start) })
+            .collect();
+
+        let records: Vec<Vec<DltIndexRecord>> = thread::scope(|s| {
+            let handles: Vec<_> = slices.iter().zip(starts.iter())
+                .map(|(slice, start)| s.spawn(move || {
+                    let base = slice.as_ptr() as usize - bytes.as_ptr() as usize;
+                    dltit_offset(slice).enumerate()
+                        .filter(|(i, _)| (start + i) % stride == 0)
+                        .map(|(i, (offset, entry))| DltIndexRecord::new(start + i, base + offset, &entry))
+                        .collect()
+                }))
+                .collect();
+            handles.into_iter().map(|h| h.join().expect("index thread panicked")).collect()
+        });
+
+        let mut header = DltIndexHeader::new_zeroed();
+        header.magic = INDEX_MAGIC;
+        header.file_size.set(bytes.len() as u64);
+        header.stride.set(stride as u32);
+        header.num_messages.set(counts.iter().sum::<usize>() as u64);
+
+        let mut buf = header.as_bytes().to_vec();
+        for record in records.iter().flatten() {
+            buf.extend_from_slice(record.as_bytes());
+        }
+        DltIndex { buffer: DltBuffer::Read(buf) }
+    }
+
+    /// Builds the index of the DLT file ``filename`` and writes it to ``<filename>.idx``.
+    /// Compressed files are rejected, since the offsets of the index refer to the file itself.
+    pub fn build_file(filename: &str, stride: usize, num: usize) -> Result<Self, io::Error> {
+        let (size, mtime) = file_id(filename)?;
+        let buf = DltBuffer::mmap_file(filename)?;
+        if Compression::detect(buf.as_slice()) != Compression::None {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "compressed DLT files cannot be indexed"));
+        }
+        let mut index = Self::build(buf.as_slice(), stride, num);
+        if size != buf.len() as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "file changed while building the index"));
+        }
+
+        if let DltBuffer::Read(bytes) = &mut index.buffer {
+            let header = DltIndexHeader::mut_from_prefix(bytes).expect("valid index header");
+            header.mtime_secs.set(mtime.0);
+            header.mtime_nanos.set(mtime.1);
+        }
+        let mut writer = BufWriter::new(File::create(index_filename(filename))?);
+        writer.write_all(index.buffer.as_slice())?;
+        writer.flush()?;
+        Ok(index)
+    }
+
+    /// Opens ``<filename>.idx`` and validates it against size and modification time of the DLT file ``filename``.
+    pub fn open(filename: &str) -> Result<Self, io::Error> {
+        let index = DltIndex { buffer: DltBuffer::mmap_file(index_filename(filename).to_str().unwrap_or_default())? };
+        let header = DltIndexHeader::ref_from_prefix(index.buffer.as_slice())
+            .filter(|header| header.magic == INDEX_MAGIC)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a DLT index"))?;
+
+        let (size, mtime) = file_id(filename)?;
+        if header.file_size.get() != size || (header.mtime_secs.get(), header.mtime_nanos.get()) != mtime {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "index is outdated"));
+        }
+        if !(index.buffer.len() - mem::size_of::<DltIndexHeader>()).is_multiple_of(mem::size_of::<DltIndexRecord>()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "index is truncated"));
+        }
+        Ok(index)
+    }
+
+    /// Opens the index of ``filename``, the index is built if it does not exist or is outdated.
+    pub fn open_or_build(filename: &str, stride: usize, num: usize) -> Result<Self, io::Error> {
+        Self::open(filename).or_else(|_| Self::build_file(filename, stride, num))
+    }
+
+    pub fn header(&self) -> &DltIndexHeader {
+        DltIndexHeader::ref_from_prefix(self.buffer.as_slice()).expect("valid index header")
+    }
+
+    pub fn records(&self) -> &[DltIndexRecord] {
+        DltIndexRecord::slice_from(&self.buffer.as_slice()[mem::size_of::<DltIndexHeader>()..]).expect("valid index records")
+    }
+
+    ///
+    /// ``time_range``: returns the range of the DLT file that contains all messages with storage time
+    /// in ``[from_us, to_us]``, in microseconds since 1.1.1970.
+    ///
+    /// The range starts at the indexed message preceding the first indexed message at or after ``from_us``,
+    /// or at the last indexed message if there is none, and ends at the indexed message following the last indexed message at or before ``to_us``,
+    /// so that messages that are not indexed are covered as well. The messages of the range still need to be filtered.
+    ///
+    /// The indexed messages are found by binary search, which requires ascending storage times like ``dltseek::seek_time``.
+    ///
+    pub fn time_range(&self, from_us: i64, to_us: i64) -> Range<usize> {
+        let records = self.records();
+        let file_size = self.header().file_size.get() as usize;
+
+        // the messages after the last indexed message are not indexed, they may still be at or after from_us
+        let start = match records.partition_point(|r| r.timestamp_us() < from_us) {
+            0 => 0,
+            i => records[i - 1].offset.get() as usize,
+        };
+        let end = match records.partition_point(|r| r.timestamp_us() <= to_us) {
+            0 => return start..start,
+            i => records.get(i).map(|r| r.offset.get() as usize).unwrap_or(file_size),
+        };
+        start..end.max(start)
+    }
+
+    /// Returns the indexed messages of application ``apid``. Only if the index was built with stride 1,
+    /// all messages of ``apid`` are found.
+    pub fn find_apid<'a>(&'a self, apid: &'a [u8; 4]) -> impl Iterator<Item = &'a DltIndexRecord> + 'a {
+        self.records().iter().filter(move |r| r.apid == *apid)
+    }
+}
+
+/// Returns ``<filename>.idx``.
+pub fn index_filename(filename: &str) -> PathBuf {
+    PathBuf::from(format!("{}.idx", filename))
+}
+
+fn file_id(filename: &str) -> Result<(u64, (u64, u32)), io::Error> {
+    let meta = fs::metadata(filename)?;
+    let mtime = meta.modified()?.duration_since(UNIX_EPOCH).unwrap_or_default();
+    Ok((meta.len(), (mtime.as_secs(), mtime.subsec_nanos())))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn temp_copy(dir: &TempDir, name: &str) -> String {
+        let path = dir.path().join(name);
+        fs::copy(format!("../test/{}", name), &path).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn index_build() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex004.dlt").expect("test file");
+        let sequential = DltIndex::build(buf.as_slice(), 1, 1);
+        let parallel = DltIndex::build(buf.as_slice(), 1, 7);
+        assert_eq!(52451, sequential.header().num_messages.get());
+        assert_eq!(sequential.records(), parallel.records());
+
+        let (offset, entry) = dltit_offset(buf.as_slice()).nth(1000).unwrap();
+        let record = &parallel.records()[1000];
+        assert_eq!(1000, record.ordinal.get());
+        assert_eq!(offset as u64, record.offset.get());
+        assert_eq!(entry.dlt.timestamp().unwrap(), record.tmsp.get());
+        assert_eq!(entry.dlt.extended_header().unwrap().apid, record.apid);
+        assert_eq!(*b"E001", record.ecu);
+
+        let strided = DltIndex::build(buf.as_slice(), 100, 7);
+        assert_eq!(525, strided.records().len());
+        assert!(strided.records().iter().zip(sequential.records().iter().step_by(100)).all(|(a, b)| a == b));
+
+        let num_apid = dltit(buf.as_slice()).filter(|e| e.dlt.extended_header().is_some_and(|ext| ext.apid == *b"A004")).count();
+        assert_eq!(num_apid, parallel.find_apid(b"A004").count());
+    }
+
+    #[test]
+    fn index_sidecar() {
+        let dir = TempDir::new().unwrap();
+        let fname = temp_copy(&dir, "lc_ex003.dlt");
+
+        assert!(DltIndex::open(&fname).is_err());
+        let built = DltIndex::open_or_build(&fname, 10, 4).expect("index built");
+        assert!(index_filename(&fname).exists());
+
+        let opened = DltIndex::open(&fname).expect("valid index");
+        assert_eq!(built.records(), opened.records());
+        assert_eq!(8045, opened.header().num_messages.get());
+
+        // appending data to the DLT file invalidates the index
+        OpenOptions::new().append(true).open(&fname).unwrap().write_all(b"DLT\x01").unwrap();
+        assert!(DltIndex::open(&fname).is_err());
+
+        let compressed = dir.path().join("lc_ex003.dlt.zst");
+        fs::write(&compressed, zstd::encode_all(fs::read("../test/lc_ex003.dlt").unwrap().as_slice(), 0).unwrap()).unwrap();
+        let err = DltIndex::open_or_build(compressed.to_str().unwrap(), 10, 4).err().expect("compressed file rejected");
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+
+    #[test]
+    fn index_time_range() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex002.dlt").expect("test file");
+        let index = DltIndex::build(buf.as_slice(), 50, 4);
+
+        let times: Vec<i64> = dltit(buf.as_slice()).map(|e| e.storage_header.timestamp_us()).collect();
+        let (from, to) = (times[3000], times[4000]);
+
+        let range = index.time_range(from, to);
+        let found = dltit(&buf.as_slice()[range.clone()])
+            .filter(|e| (from..=to).contains(&e.storage_header.timestamp_us()))
+            .count();
+        let expected = times.iter().filter(|t| (from..=to).contains(*t)).count();
+        assert_eq!(expected, found);
+        assert!(range.len() < buf.len() / 5);
+
+        assert_eq!(0..0, index.time_range(0, 1));
+        assert_eq!(0..buf.len(), index.time_range(0, i64::MAX));
+
+        // the messages after the last indexed message are not indexed
+        let last = index.records().last().unwrap();
+        let from = times[last.ordinal.get() as usize + 1..].iter().copied().find(|t| *t > last.timestamp_us()).unwrap();
+        let range = index.time_range(from, i64::MAX);
+        assert_eq!(last.offset.get() as usize..buf.len(), range);
+        let found = dltit(&buf.as_slice()[range]).filter(|e| e.storage_header.timestamp_us() >= from).count();
+        assert_eq!(times.iter().filter(|t| **t >= from).count(), found);
+        assert!(found > 0);
+    }
+}
@@ -0,0 +1,214 @@
+use std::{collections::BTreeMap, io};
+
+use matchit::generator::{adapter::AdapterTrait, approx::Quantiles, generator::Generator, parallel::Mergeable, reducer::{Reducer, ReducerTrait}, sequence::SequenceEvent};
+use regex::bytes::Regex;
+
+use crate::{dlt_v1::{dltit, DltStorageEntry}, dltfilter::DltFilter, dltmerge::DltEcuClock, dltseries::DltSeriesTime};
+
+/// Start or end message of a request/response pair with its time in microseconds and its correlation ID.
+#[derive(Clone, Debug)]
+struct DltLatencyMarker<'bytes> {
+    time: i64,
+    id: &'bytes [u8],
+    class: &'bytes [u8],
+    start: bool,
+    end: bool,
+}
+
+/// Key class of a ``SequenceEvent``, the class of its start message or of its unmatched end message.
+fn class<'bytes>(e: &SequenceEvent<DltLatencyMarker<'bytes>>) -> &'bytes [u8] {
+    match e {
+        SequenceEvent::Matched(start, _) | SequenceEvent::Timeout(start) | SequenceEvent::Cancelled(start, _) => start.class,
+        SequenceEvent::Unmatched(end) => end.class,
+    }
+}
+
+/// Outcome of a start or end message, see ``DltLatencyClass``.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum DltLatencyEvent {
+    /// Latency in microseconds.
+    Matched(i64),
+    UnmatchedStart,
+    UnmatchedEnd,
+}
+
+///
+/// ``DltLatencyClass``: latency distribution in microseconds and the number of unmatched start and end messages of a
+/// key class.
+///
+#[derive(Clone, Debug)]
+pub struct DltLatencyClass {
+    pub latencies: Quantiles,
+    pub unmatched_starts: usize,
+    pub unmatched_ends: usize,
+}
+
+impl DltLatencyClass {
+    pub fn new() -> Self {
+        Self { latencies: Quantiles::new(200), unmatched_starts: 0, unmatched_ends: 0 }
+    }
+}
+
+impl Default for DltLatencyClass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReducerTrait for DltLatencyClass {
+    type Input = DltLatencyEvent;
+    type Reduced = DltLatencyClass;
+
+    fn next(&mut self, next: Self::Input) {
+        match next {
+            DltLatencyEvent::Matched(latency) => self.latencies.next(latency as f64),
+            DltLatencyEvent::UnmatchedStart => self.unmatched_starts += 1,
+            DltLatencyEvent::UnmatchedEnd => self.unmatched_ends += 1,
+        }
+    }
+
+    fn finalize(self) -> Self::Reduced {
+        self
+    }
+}
+
+impl Mergeable for DltLatencyClass {
+    fn merge(&mut self, other: Self) {
+        self.latencies.merge(other.latencies);
+        self.unmatched_starts += other.unmatched_starts;
+        self.unmatched_ends += other.unmatched_ends;
+    }
+}
+
+///
+/// ``DltLatency``: measures the latency between start and end messages, e.g., requests and their responses.
+///
+/// Start messages match ``start``, end messages match ``end``. A start and an end message belong together if ``key``
+/// finds the same correlation ID in both payloads: the capture group ``id``, or the first capture group, or the whole
+/// match. The optional capture group ``class`` of the start message, e.g., the request type, selects the key class
+/// the latency is reported for. An end message is matched with the oldest pending start with the same ID; starts
+/// without end within ``timeout`` are unmatched starts, ends without pending start are unmatched ends.
+///
+/// With ``DltSeriesTime::Ecu``, starts and ends are only matched within the same lifecycle of the same ECU, as
+/// detected by ``DltEcuClock``. With ``DltSeriesTime::Storage``, messages of all ECUs are matched.
+///
+pub struct DltLatency {
+    pub start: DltFilter,
+    pub end: DltFilter,
+    pub key: Regex,
+    pub time: DltSeriesTime,
+    /// Timeout in microseconds, 10 seconds by default.
+    pub timeout: i64,
+}
+
+impl DltLatency {
+    pub fn new(start: DltFilter, end: DltFilter, key: &str, time: DltSeriesTime) -> Result<Self, io::Error> {
+        let key = Regex::new(key).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        Ok(Self { start, end, key, time, timeout: 10_000_000 })
+    }
+
+    fn marker<'bytes>(&self, entry: &DltStorageEntry<'bytes>, time: i64) -> Option<DltLatencyMarker<'bytes>> {
+        let start = self.start.matches(entry);
+        let end = self.end.matches(entry);
+        if !start && !end {
+            return None
+        }
+        let captures = self.key.captures(entry.dlt.payload()?)?;
+        let id = captures.name("id").or_else(|| captures.get(1)).or_else(|| captures.get(0))?.as_bytes();
+        let class = captures.name("class").map(|c| c.as_bytes()).unwrap_or_default();
+        Some(DltLatencyMarker { time, id, class, start, end })
+    }
+
+    /// Returns the latencies of the messages of ``bytes`` per key class.
+    pub fn analyze(&self, bytes: &[u8]) -> BTreeMap<Vec<u8>, DltLatencyClass> {
+        let event = |e: &SequenceEvent<DltLatencyMarker>| match e {
+            SequenceEvent::Matched(start, end) => DltLatencyEvent::Matched(end.time - start.time),
+            SequenceEvent::Timeout(_) | SequenceEvent::Cancelled(_, _) => DltLatencyEvent::UnmatchedStart,
+            SequenceEvent::Unmatched(_) => DltLatencyEvent::UnmatchedEnd,
+        };
+        // a state machine per lifecycle, since ECU timestamps of different lifecycles are not comparable
+        let mut lifecycles = Generator::split(|m: &(usize, DltLatencyMarker)| m.0,
+            |_| Generator::map(|m: &(usize, DltLatencyMarker)| m.1.clone())
+                .sequence(|m| m.start, |m| m.end, |_| false, |m| m.id, |m| m.time, self.timeout)
+                .split(class, |_| Reducer::new(DltLatencyClass::new(), Generator::map(event))));
+
+        let mut clock = DltEcuClock::default();
+        for entry in dltit(bytes) {
+            let (lifecycle, time) = match self.time {
+                DltSeriesTime::Storage => (0, entry.storage_header.timestamp_us()),
+                DltSeriesTime::Ecu => match (clock.lifecycle(&entry), entry.dlt.timestamp()) {
+                    (Some(lifecycle), Some(timestamp)) => (lifecycle, timestamp as i64 * 100),
+                    _ => continue,
+                },
+            };
+            if let Some(marker) = self.marker(&entry, time) {
+                lifecycles.next((lifecycle, marker));
+            }
+        }
+
+        let mut result: BTreeMap<Vec<u8>, DltLatencyClass> = BTreeMap::new();
+        for (_, classes) in lifecycles.finalize() {
+            Mergeable::merge(&mut result, classes.into_iter().map(|(class, latencies)| (class.to_vec(), latencies)).collect());
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dlttest::{string, TestMessage};
+
+    use super::*;
+
+    fn message(secs: u32, ecu: &[u8; 4], timestamp: u32, text: &str) -> Vec<u8> {
+        TestMessage { secs, ecu: *ecu, with_ecu_id: true, timestamp: Some(timestamp), extended: Some((1, *b"APP\0", *b"CTX\0")), ..Default::default() }
+            .bytes(&string(text))
+    }
+
+    #[test]
+    fn latency() {
+        let bytes = [
+            message(1, b"ECU1", 10000, "request get id=1"),
+            message(1, b"ECU1", 10500, "request set id=2"),
+            message(1, b"ECU1", 11000, "response id=1"),
+            message(1, b"ECU1", 13500, "response id=2"),
+            message(1, b"ECU1", 14000, "response id=3"),
+            // restart of ECU1, the pending request is not matched with the response of the next lifecycle
+            message(2, b"ECU1", 200000, "request get id=4"),
+            message(3, b"ECU1", 100, "response id=4"),
+            message(3, b"ECU2", 20000, "request get id=5"),
+            message(3, b"ECU2", 20100, "response id=5"),
+            // concurrent applications of ECU2 log a slightly older timestamp, which does not start a new lifecycle
+            message(3, b"ECU2", 20500, "request get id=6"),
+            message(3, b"ECU2", 20300, "log of another application"),
+            message(3, b"ECU2", 20700, "response id=6"),
+        ].concat();
+
+        let start = DltFilter::parse(r#"payload =~ /request/"#).unwrap();
+        let end = DltFilter::parse(r#"payload =~ /response/"#).unwrap();
+        let latency = DltLatency::new(start, end, r"(?:request (?P<class>\w+) )?id=(?P<id>\d+)", DltSeriesTime::Ecu).unwrap();
+        let result = latency.analyze(&bytes);
+
+        let get = &result[b"get".as_slice()];
+        assert_eq!(3, get.latencies.count());
+        assert_eq!(Some(10000.0), get.latencies.quantile(0.0));
+        assert_eq!(Some(100000.0), get.latencies.quantile(1.0));
+        assert_eq!(1, get.unmatched_starts);
+        let set = &result[b"set".as_slice()];
+        assert_eq!(Some(300000.0), set.latencies.quantile(0.5));
+        // responses have no class
+        assert_eq!(2, result[b"".as_slice()].unmatched_ends);
+
+        // storage time across ECUs: the response of id 4 is matched, all latencies are 0 or 1 seconds
+        let mut latency = latency;
+        latency.time = DltSeriesTime::Storage;
+        let result = latency.analyze(&bytes);
+        assert_eq!(4, result[b"get".as_slice()].latencies.count());
+        assert_eq!(0, result[b"get".as_slice()].unmatched_starts);
+        assert_eq!(1, result[b"".as_slice()].unmatched_ends);
+
+        latency.timeout = 500_000;
+        let result = latency.analyze(&bytes);
+        assert_eq!(1, result[b"get".as_slice()].unmatched_starts);
+    }
+}
@@ -0,0 +1,198 @@
+use std::{collections::BTreeMap, io::{self, Write}};
+
+use matchit::{mergeit::MergeIterator, WithOffset};
+
+use crate::dlt_v1::{dltit_offset, DltStorageEntry};
+
+/// Time used to order messages by ``dltmerge``.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum DltMergeTime {
+    /// Storage header time, i.e., the time a logger received the message.
+    Storage,
+    /// ECU timestamp corrected by the estimated boot time of the ECU, see ``DltEcuClock``.
+    CorrectedEcu,
+}
+
+///
+/// ``DltEcuClock``: maps the ECU timestamp (0.1 milliseconds since boot) of a message to the wall clock.
+///
+/// The boot time is estimated per ECU as the minimal difference between storage time and ECU timestamp seen so far,
+/// because transmission delays only increase this difference. An ECU timestamp more than ``LIFECYCLE_JITTER`` below
+/// the highest timestamp of the lifecycle of its ECU starts a new lifecycle with a new estimation; smaller steps back
+/// are common, since applications log concurrently. Messages without ECU timestamp use the storage time.
+///
+#[derive(Default)]
+pub struct DltEcuClock {
+    // ECU ID -> (lifecycle, highest ECU timestamp, boot time)
+    ecus: BTreeMap<[u8; 4], (usize, u32, i64)>,
+    lifecycles: usize,
+}
+
+/// Decrease of the ECU timestamp (0.1 milliseconds) that is tolerated within a lifecycle, see ``DltEcuClock``.
+pub const LIFECYCLE_JITTER: u32 = 100_000;
+
+impl DltEcuClock {
+    /// Returns the lifecycle and the corrected ECU time in microseconds since 1.1.1970, if ``entry`` has a timestamp.
+    fn update(&mut self, entry: &DltStorageEntry) -> Option<(usize, i64)> {
+        let tmsp = entry.dlt.timestamp()?;
+        let storage = entry.storage_header.timestamp_us();
+        let id = entry.dlt.ecu_id().map(|id| id.to_be_bytes()).unwrap_or(entry.storage_header.ecu);
+        let ecu = tmsp as i64 * 100;
+        let state = match self.ecus.get(&id) {
+            Some((lifecycle, max, boot)) if tmsp.saturating_add(LIFECYCLE_JITTER) >= *max => (*lifecycle, tmsp.max(*max), (*boot).min(storage - ecu)),
+            _ => {
+                self.lifecycles += 1;
+                (self.lifecycles - 1, tmsp, storage - ecu)
+            },
+        };
+        self.ecus.insert(id, state);
+        Some((state.0, state.2 + ecu))
+    }
+
+    /// Returns the corrected ECU time of ``entry`` in microseconds since 1.1.1970.
+    pub fn corrected_us(&mut self, entry: &DltStorageEntry) -> i64 {
+        self.update(entry).map(|(_, time)| time).unwrap_or_else(|| entry.storage_header.timestamp_us())
+    }
+
+    /// Returns the lifecycle of ``entry``, numbered over all ECUs from ``0``, or ``None`` if it has no timestamp.
+    pub fn lifecycle(&mut self, entry: &DltStorageEntry) -> Option<usize> {
+        self.update(entry).map(|(lifecycle, _)| lifecycle)
+    }
+
+    /// Number of lifecycles seen so far, of all ECUs.
+    pub fn lifecycles(&self) -> usize {
+        self.lifecycles
+    }
+}
+
+///
+/// ``dltmerge``: merges the DLT messages of several slices, e.g., files recorded by separate loggers, ordered by ``time``.
+///
+/// Messages with equal time are returned in the order of ``slices``. Each message is returned with
+/// ``(slice index, offset)``. Only one message per slice is kept in memory, so that memory-mapped files of any size
+/// can be merged.
+///
+pub fn dltmerge<'bytes>(slices: &[&'bytes [u8]], time: DltMergeTime) -> impl Iterator<Item = ((usize, usize), DltStorageEntry<'bytes>)> {
+    let iters = slices.iter().map(|slice| dltit_offset(slice)).collect();
+    let mut clocks: Vec<DltEcuClock> = slices.iter().map(|_| DltEcuClock::default()).collect();
+
+    MergeIterator::new(iters, move |index, (_, entry): &WithOffset<DltStorageEntry>| {
+        match time {
+            DltMergeTime::Storage => entry.storage_header.timestamp_us(),
+            DltMergeTime::CorrectedEcu => clocks[index].corrected_us(entry),
+        }
+    })
+    .map(|(index, (offset, entry))| ((index, offset), entry))
+}
+
+/// Writes the messages of ``slices`` ordered by ``time`` to ``writer``, which results in a valid DLT file.
+/// Returns the number of messages.
+pub fn dltmerge_to<W: Write>(slices: &[&[u8]], time: DltMergeTime, writer: &mut W) -> Result<usize, io::Error> {
+    let mut num = 0;
+    for (_, entry) in dltmerge(slices, time) {
+        entry.write_to(writer)?;
+        num += 1;
+    }
+    Ok(num)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{dlt_v1::dltit, dltbuffer::DltBuffer};
+
+    use super::*;
+
+    #[test]
+    fn merge_storage_time() {
+        let b1 = DltBuffer::mmap_file("../test/lc_ex002.dlt").expect("test file");
+        let b2 = DltBuffer::mmap_file("../test/lc_ex006.dlt").expect("test file");
+        let b3 = DltBuffer::mmap_file("../test/68b_verbose.dlt").expect("test file");
+        let slices = [b1.as_slice(), b2.as_slice(), b3.as_slice()];
+
+        let merged: Vec<((usize, usize), i64)> = dltmerge(&slices, DltMergeTime::Storage)
+            .map(|(pos, e)| (pos, e.storage_header.timestamp_us()))
+            .collect();
+        assert_eq!(11696 + 10001 + 10000, merged.len());
+        // 68b_verbose.dlt has storage time 0
+        assert!(merged[..10000].iter().all(|((index, _), _)| *index == 2));
+
+        // each input keeps its order
+        for (index, slice) in slices.iter().enumerate() {
+            let offsets: Vec<usize> = merged.iter().filter(|((i, _), _)| *i == index).map(|((_, offset), _)| *offset).collect();
+            let expected: Vec<usize> = dltit_offset(slice).map(|(offset, _)| offset).collect();
+            assert_eq!(expected, offsets);
+        }
+    }
+
+    #[test]
+    fn merge_sorted() {
+        // lc_ex002.dlt has monotonic storage times, split it into two interleaved inputs
+        let buf = DltBuffer::mmap_file("../test/lc_ex002.dlt").expect("test file");
+        let (mut even, mut odd) = (vec![], vec![]);
+        for (i, e) in dltit(buf.as_slice()).enumerate() {
+            e.write_to(if i % 2 == 0 { &mut even } else { &mut odd }).unwrap();
+        }
+
+        let mut out = vec![];
+        assert_eq!(11696, dltmerge_to(&[&odd, &even], DltMergeTime::Storage, &mut out).unwrap());
+        let times: Vec<i64> = dltit(&out).map(|e| e.storage_header.timestamp_us()).collect();
+        assert!(times.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(buf.len(), out.len());
+    }
+
+    #[test]
+    fn merge_ecu_time() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex004.dlt").expect("test file");
+        let mut clock = DltEcuClock::default();
+        for e in dltit(buf.as_slice()).filter(|e| e.dlt.timestamp().is_some()) {
+            let corrected = clock.corrected_us(&e);
+            assert!(corrected <= e.storage_header.timestamp_us());
+        }
+        // the timestamps of lc_ex004.dlt step back by less than a second 136 times
+        assert_eq!(1, clock.lifecycles());
+
+        let merged = dltmerge(&[buf.as_slice(), buf.as_slice()], DltMergeTime::CorrectedEcu).count();
+        assert_eq!(2 * 52451, merged);
+    }
+
+    #[test]
+    fn ecu_clock_lifecycles() {
+        let lifecycles = |bytes: &[u8]| {
+            let mut clock = DltEcuClock::default();
+            let lifecycles: Vec<Option<usize>> = dltit(bytes).map(|e| clock.lifecycle(&e)).collect();
+            (clock.lifecycles(), lifecycles)
+        };
+        // lc_ex002.dlt (ECU E002) restarts once, lc_ex005.dlt (ECU E001) restarts once after a few seconds of jitter
+        let b2 = DltBuffer::mmap_file("../test/lc_ex002.dlt").expect("test file");
+        let b5 = DltBuffer::mmap_file("../test/lc_ex005.dlt").expect("test file");
+        let (num2, lifecycles2) = lifecycles(b2.as_slice());
+        let (num5, lifecycles5) = lifecycles(b5.as_slice());
+        assert_eq!((2, 2), (num2, num5));
+        assert!(lifecycles5.windows(2).all(|w| w[0] <= w[1] || w[1].is_none()));
+
+        // interleaved messages of both ECUs keep their lifecycles
+        let mut interleaved = vec![];
+        let mut expected = vec![];
+        let (mut it2, mut it5) = (dltit(b2.as_slice()).zip(lifecycles2), dltit(b5.as_slice()).zip(lifecycles5));
+        loop {
+            let next = [it2.next(), it5.next()];
+            if next.iter().all(Option::is_none) {
+                break
+            }
+            for (i, (e, lifecycle)) in next.into_iter().enumerate().filter_map(|(i, n)| n.map(|n| (i, n))) {
+                e.write_to(&mut interleaved).unwrap();
+                expected.push(lifecycle.map(|l| (i, l)));
+            }
+        }
+        let (num, lifecycles) = lifecycles(&interleaved);
+        assert_eq!(4, num);
+        // lifecycles are numbered in the order of their first message
+        let mut numbers = BTreeMap::new();
+        for (expected, lifecycle) in expected.into_iter().zip(lifecycles) {
+            if let (Some(expected), Some(lifecycle)) = (expected, lifecycle) {
+                assert_eq!(lifecycle, *numbers.entry(expected).or_insert(lifecycle));
+            }
+        }
+        assert_eq!(vec![0, 1, 2, 3], numbers.values().copied().collect::<std::collections::BTreeSet<usize>>().into_iter().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,241 @@
+use std::{cmp::Ordering, io};
+
+use matchit::searchable::SearchableMarkerTrait;
+
+use crate::{dlt_v1::{dltit, dltit_offset, DltStorageEntry}, dltbuffer::DltBuffer, DltIterator};
+
+/// Order of the files in a ``DltMultiBuffer``.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum DltFileOrder {
+    /// Natural order of the file names, i.e., ``log.dlt.2`` is ordered before ``log.dlt.10``.
+    Name,
+    /// Storage header timestamp of the first message, files without any message are ordered last.
+    StorageTime,
+    /// Order in which the files are given.
+    Explicit,
+}
+
+///
+/// ``DltMultiBuffer``: presents an ordered set of DLT files, e.g., rotated log files, as one logical recording.
+///
+/// In contrast to concatenating the files, e.g., with ``cat``, each file is kept in its own ``DltBuffer``.
+/// A truncated message at the end of a file is therefore never glued to the first message of the next file.
+/// Messages are identified by ``(file index, offset)``.
+///
+pub struct DltMultiBuffer {
+    names: Vec<String>,
+    buffers: Vec<DltBuffer>,
+}
+
+impl DltMultiBuffer {
+    /// Opens ``filenames`` with ``DltBuffer::open`` and orders them according to ``order``.
+    pub fn open<S: AsRef<str>>(filenames: &[S], order: DltFileOrder) -> Result<Self, io::Error> {
+        let mut files = filenames.iter()
+            .map(|name| Ok((name.as_ref().to_string(), DltBuffer::open(name.as_ref())?)))
+            .collect::<Result<Vec<_>, io::Error>>()?;
+
+        match order {
+            DltFileOrder::Name => files.sort_by(|a, b| natural_cmp(&a.0, &b.0)),
+            DltFileOrder::StorageTime => files.sort_by_cached_key(|(_, buf)| {
+                let first = dltit(buf.as_slice()).next()
+                    .map(|e| (e.storage_header.secs.get(), e.storage_header.msecs.get()));
+                (first.is_none(), first)
+            }),
+            DltFileOrder::Explicit => {},
+        }
+
+        let (names, buffers) = files.into_iter().unzip();
+        Ok(Self { names, buffers })
+    }
+
+    /// Opens all files matching the glob ``pattern``, e.g., ``logs/*.dlt*``.
+    pub fn open_glob(pattern: &str, order: DltFileOrder) -> Result<Self, io::Error> {
+        let filenames = glob::glob(pattern)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+            .map(|path| path.map(|p| p.to_string_lossy().to_string()).map_err(io::Error::from))
+            .collect::<Result<Vec<_>, io::Error>>()?;
+        Self::open(&filenames, order)
+    }
+
+    /// Number of files.
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    /// File names in the order of the file indices.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Returns the contents of the file with ``index``, e.g., to be used with ``dltit``.
+    pub fn as_slice(&self, index: usize) -> &[u8] {
+        self.buffers[index].as_slice()
+    }
+
+    /// Returns the contents of all files in order.
+    pub fn slices(&self) -> Vec<&[u8]> {
+        self.buffers.iter().map(|buf| buf.as_slice()).collect()
+    }
+
+    /// Iterates over the messages of all files, each message is returned with ``(file index, offset)``.
+    pub fn iter(&self) -> DltMultiIterator<'_> {
+        DltMultiIterator::new(self.slices())
+    }
+
+    ///
+    /// ``partition::<T>``: partitions each file with ``DltBuffer::partition::<T>``, the ``num`` partitions are distributed
+    /// among the files according to their size, but at least one partition is used per file. Partitions never span file
+    /// boundaries.
+    ///
+    /// Returns each partition with ``(file index, offset of the partition within the file)``.
+    ///
+    pub fn partition<'bytes, T: SearchableMarkerTrait<'bytes>>(&'bytes self, num: usize) -> Vec<((usize, usize), &'bytes [u8])> {
+        let total: usize = self.buffers.iter().map(|buf| buf.len()).sum::<usize>().max(1);
+
+        let mut result = vec![];
+        for (index, buf) in self.buffers.iter().enumerate() {
+            let start = buf.as_slice().as_ptr() as usize;
+            let file_num = (num * buf.len()).div_ceil(total).max(1);
+            for slice in buf.partition::<T>(file_num) {
+                result.push(((index, slice.as_ptr() as usize - start), slice));
+            }
+        }
+        result
+    }
+}
+
+///
+/// ``DltMultiIterator``: iterates over the DLT messages of several slices, see ``DltMultiBuffer::iter``.
+///
+pub struct DltMultiIterator<'bytes> {
+    slices: Vec<&'bytes [u8]>,
+    index: usize,
+    it: Option<DltIterator<'bytes, DltStorageEntry<'bytes>>>,
+}
+
+impl<'bytes> DltMultiIterator<'bytes> {
+    pub fn new(slices: Vec<&'bytes [u8]>) -> Self {
+        let it = slices.first().map(|slice| dltit_offset(slice));
+        Self { slices, index: 0, it }
+    }
+}
+
+impl<'bytes> Iterator for DltMultiIterator<'bytes> {
+    type Item = ((usize, usize), DltStorageEntry<'bytes>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((offset, entry)) = self.it.as_mut()?.next() {
+                return Some(((self.index, offset), entry));
+            }
+            self.index += 1;
+            self.it = self.slices.get(self.index).map(|slice| dltit_offset(slice));
+        }
+    }
+}
+
+/// Compares names with runs of digits compared by their numeric value.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.as_bytes();
+    let mut b = b.as_bytes();
+    loop {
+        match (a.first(), b.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let len_a = a.iter().take_while(|c| c.is_ascii_digit()).count();
+                let len_b = b.iter().take_while(|c| c.is_ascii_digit()).count();
+                let (num_a, num_b) = (trim_zeros(&a[..len_a]), trim_zeros(&b[..len_b]));
+                let ord = num_a.len().cmp(&num_b.len()).then(num_a.cmp(num_b));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+                a = &a[len_a..];
+                b = &b[len_b..];
+            },
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(y);
+                }
+                a = &a[1..];
+                b = &b[1..];
+            },
+        }
+    }
+}
+
+fn trim_zeros(digits: &[u8]) -> &[u8] {
+    let zeros = digits.iter().take_while(|c| **c == b'0').count();
+    &digits[zeros..]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn multi_iter() {
+        let files = ["../test/lc_ex002.dlt", "../test/lc_ex003.dlt", "../test/lc_ex006.dlt"];
+        let multi = DltMultiBuffer::open(&files, DltFileOrder::Explicit).expect("test files");
+
+        let entries: Vec<(usize, usize)> = multi.iter().map(|(pos, _)| pos).collect();
+        assert_eq!(11696 + 8045 + 10001, entries.len());
+        assert_eq!((1, 0), entries[11696]);
+        assert_eq!((2, 0), entries[11696 + 8045]);
+
+        let count: usize = multi.partition::<DltStorageEntry>(8).iter()
+            .map(|((index, offset), slice)| {
+                assert_eq!(&multi.as_slice(*index)[*offset..*offset + slice.len()], *slice);
+                dltit(slice).count()
+            })
+            .sum();
+        assert_eq!(entries.len(), count);
+    }
+
+    #[test]
+    fn multi_truncated() {
+        let dir = TempDir::new().unwrap();
+        let bytes = std::fs::read("../test/lc_ex004.dlt").unwrap();
+        let (second, _) = dltit_offset(&bytes).nth(1).unwrap();
+
+        // the first file ends with a truncated message, cat would glue it to the first message of the next file
+        let truncated = dir.path().join("log.dlt.2");
+        std::fs::File::create(&truncated).unwrap().write_all(&bytes[..second + 20]).unwrap();
+        let complete = dir.path().join("log.dlt.10");
+        std::fs::File::create(&complete).unwrap().write_all(&bytes).unwrap();
+
+        let pattern = dir.path().join("log.dlt.*");
+        let multi = DltMultiBuffer::open_glob(pattern.to_str().unwrap(), DltFileOrder::Name).expect("valid files");
+        assert!(multi.names()[0].ends_with("log.dlt.2"));
+        assert_eq!(1 + 52451, multi.iter().count());
+        assert_eq!(Some((1, 0)), multi.iter().map(|(pos, _)| pos).nth(1));
+    }
+
+    #[test]
+    fn multi_storage_time() {
+        let files = ["../test/93b_verbose.dlt", "../test/lc_ex003.dlt", "../test/lc_ex002.dlt"];
+        let multi = DltMultiBuffer::open(&files, DltFileOrder::StorageTime).expect("test files");
+
+        let first: Vec<u32> = multi.slices().iter()
+            .map(|slice| dltit(slice).next().unwrap().storage_header.secs.get())
+            .collect();
+        assert!(first.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!("../test/93b_verbose.dlt", multi.names()[0]);
+    }
+
+    #[test]
+    fn natural_order() {
+        let mut names = vec!["log.dlt.10", "log.dlt.2", "log.dlt", "log.dlt.002a", "log.dlt.1"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(vec!["log.dlt", "log.dlt.1", "log.dlt.2", "log.dlt.002a", "log.dlt.10"], names);
+    }
+}
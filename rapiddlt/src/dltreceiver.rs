@@ -0,0 +1,167 @@
+use std::{io::{self, Write}, net::{Ipv4Addr, SocketAddr, ToSocketAddrs, UdpSocket}, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use matchit::{readit::ReadIterator, searchable::SearchableMarkerTrait, FromBytesReadableTrait};
+use zerocopy::AsBytes;
+
+use crate::dlt_v1::{DltEntry, DltStorageEntry, DltStorageHeader};
+
+///
+/// ``DltUdpReceiver``: receives DLT messages sent via UDP (unicast or multicast).
+///
+/// Each datagram may contain one or more DLT messages without storage header.
+/// A storage header with the reception time and the ECU ID is added to every message,
+/// so that the received messages can be handled like any other ``DltStorageEntry``.
+///
+pub struct DltUdpReceiver {
+    socket: UdpSocket,
+    ecu: [u8;4],
+    datagram: Vec<u8>,
+    storage: Vec<u8>,
+}
+
+impl DltUdpReceiver {
+    /// Binds a UDP socket to ``addr``. To receive multicast messages, bind to the port of the group,
+    /// e.g., ``0.0.0.0:3490``, and join the group with ``DltUdpReceiver::join_multicast_v4``.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> Result<Self, io::Error> {
+        let socket = UdpSocket::bind(addr)?;
+        Ok(Self {
+            socket,
+            ecu: [0u8;4],
+            datagram: vec![0u8; DltEntry::max_len() + 1],
+            storage: Vec::with_capacity(DltStorageEntry::max_len()),
+        })
+    }
+
+    /// Sets the ECU ID written to the storage header of messages that do not carry an ECU ID themselves.
+    pub fn with_ecu(mut self, ecu: [u8;4]) -> Self {
+        self.ecu = ecu;
+        self
+    }
+
+    pub fn join_multicast_v4(&self, group: &Ipv4Addr, interface: &Ipv4Addr) -> Result<(), io::Error> {
+        self.socket.join_multicast_v4(group, interface)
+    }
+
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<(), io::Error> {
+        self.socket.set_read_timeout(timeout)
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, io::Error> {
+        self.socket.local_addr()
+    }
+
+    /// Receives a single datagram and calls ``f`` for every DLT message it contains.
+    /// Returns the number of DLT messages.
+    pub fn recv<F>(&mut self, mut f: F) -> Result<usize, io::Error>
+    where
+        F: FnMut(DltStorageEntry)
+    {
+        self.recv_raw(|bytes| {
+            if let Some((_, entry)) = DltStorageEntry::try_read_valid_marker(bytes) {
+                f(entry)
+            }
+        })
+    }
+
+    /// Receives a single datagram and writes every DLT message it contains to ``writer``,
+    /// which results in a valid DLT file. Returns the number of DLT messages.
+    pub fn recv_to<W: Write>(&mut self, writer: &mut W) -> Result<usize, io::Error> {
+        let mut result = Ok(());
+        let num = self.recv_raw(|bytes| {
+            if result.is_ok() {
+                result = writer.write_all(bytes);
+            }
+        })?;
+        result?;
+        Ok(num)
+    }
+
+    fn recv_raw<F>(&mut self, mut f: F) -> Result<usize, io::Error>
+    where
+        F: FnMut(&[u8])
+    {
+        let (size, _) = self.socket.recv_from(&mut self.datagram)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+
+        let mut num = 0;
+        for (offset, entry) in ReadIterator::<DltEntry>::new(&self.datagram[..size], 0) {
+            let ecu = match entry.ecu_id() {
+                Some(id) => id.to_be_bytes(),
+                None => self.ecu,
+            };
+            let sh = DltStorageHeader::new(
+                [b'D',b'L',b'T', 0x1],
+                now.as_secs() as u32,
+                now.subsec_micros() as i32,
+                ecu);
+
+            self.storage.clear();
+            self.storage.extend_from_slice(sh.as_bytes());
+            self.storage.extend_from_slice(&self.datagram[offset..offset + entry.len()]);
+            f(&self.storage);
+            num += 1;
+        }
+        Ok(num)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{dlt_v1::dltit, dltbuffer::DltBuffer};
+
+    use super::*;
+
+    const GROUP: Ipv4Addr = Ipv4Addr::new(239, 255, 42, 99);
+
+    fn datagram(buf: &DltBuffer, num: usize) -> Vec<u8> {
+        let mut datagram = vec![];
+        for e in dltit(buf.as_slice()).take(num) {
+            datagram.extend_from_slice(e.dlt.header.as_bytes());
+            datagram.extend_from_slice(e.dlt.tail);
+        }
+        datagram
+    }
+
+    #[test]
+    fn multicast_loopback() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex002.dlt").expect("test file");
+        let datagram = datagram(&buf, 3);
+
+        let mut receiver = DltUdpReceiver::bind((Ipv4Addr::UNSPECIFIED, 0)).expect("bind receiver").with_ecu(*b"RECV");
+        receiver.join_multicast_v4(&GROUP, &Ipv4Addr::UNSPECIFIED).expect("join multicast group");
+        receiver.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let port = receiver.local_addr().unwrap().port();
+
+        let sender = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).unwrap();
+        sender.set_multicast_loop_v4(true).unwrap();
+        sender.send_to(&datagram, (GROUP, port)).expect("send datagram");
+
+        let mut received = vec![];
+        let num = receiver.recv(|e| received.push((e.storage_header.ecu, e.dlt.header.length(), e.storage_header.secs.get()))).expect("receive datagram");
+
+        assert_eq!(3, num);
+        let expected: Vec<usize> = dltit(buf.as_slice()).take(3).map(|e| e.dlt.header.length()).collect();
+        assert_eq!(expected, received.iter().map(|r| r.1).collect::<Vec<_>>());
+        // lc_ex002.dlt messages do not contain an ECU ID in the standard header
+        assert!(received.iter().all(|r| r.0 == *b"RECV"));
+        assert!(received.iter().all(|r| r.2 > 0));
+    }
+
+    #[test]
+    fn recv_to_writer() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex004.dlt").expect("test file");
+        let datagram = datagram(&buf, 10);
+
+        let mut receiver = DltUdpReceiver::bind((Ipv4Addr::LOCALHOST, 0)).expect("bind receiver");
+        receiver.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let sender = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        sender.send_to(&datagram, receiver.local_addr().unwrap()).expect("send datagram");
+        sender.send_to(&datagram, receiver.local_addr().unwrap()).expect("send datagram");
+
+        let mut file: Vec<u8> = vec![];
+        assert_eq!(10, receiver.recv_to(&mut file).expect("receive datagram"));
+        assert_eq!(10, receiver.recv_to(&mut file).expect("receive datagram"));
+        assert_eq!(20, dltit(&file).count());
+        assert_eq!(datagram.len() * 2 + 20 * 16, file.len());
+    }
+}
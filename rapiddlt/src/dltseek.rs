@@ -0,0 +1,152 @@
+use std::ops::Range;
+
+use matchit::{ContainedBySearch, FromBytesReadableTrait};
+
+use crate::dlt_v1::{dltit, dltit_offset, DltStorageEntry};
+
+/// Below this size, the search range is scanned linearly.
+const LINEAR_SCAN_SIZE: usize = 64 * 1024;
+/// Number of consecutive messages whose median storage time is used for a probe.
+const PROBE_LEN: usize = 7;
+
+///
+/// ``resync``: returns the offset of the first valid DLT message that starts at or after ``offset``,
+/// or ``bytes.len()`` if there is none.
+///
+/// An offset pointing into a message, e.g., into a payload containing a DLT marker, is resolved with
+/// ``ContainedBySearch::contained_by`` to the end of the containing message.
+///
+pub fn resync(search: &ContainedBySearch<DltStorageEntry>, bytes: &[u8], offset: usize) -> usize {
+    if offset >= bytes.len() {
+        return bytes.len()
+    }
+    let start = match search.contained_by(bytes, (offset, offset + 1)) {
+        Some((container, entry)) => container + entry.len(),
+        None => offset,
+    };
+    match dltit_offset(&bytes[start.min(bytes.len())..]).next() {
+        Some((pos, _)) => start + pos,
+        None => bytes.len(),
+    }
+}
+
+/// Median storage time of up to ``PROBE_LEN`` messages starting at ``offset``, which tolerates single
+/// messages with out-of-order storage times.
+fn probe(bytes: &[u8], offset: usize) -> Option<i64> {
+    let mut times: Vec<i64> = dltit(&bytes[offset..]).take(PROBE_LEN).map(|e| e.storage_header.timestamp_us()).collect();
+    if times.is_empty() {
+        return None
+    }
+    times.sort_unstable();
+    Some(times[times.len() / 2])
+}
+
+///
+/// ``seek_time``: returns the offset of the first DLT message with storage time at or after ``target_us``,
+/// in microseconds since 1.1.1970, or ``bytes.len()`` if there is none.
+///
+/// The search probes offsets by bisection and resynchronizes each probe on a valid message with ``resync``.
+/// Storage times only need to be mostly monotonic: each probe uses the median time of a few consecutive messages,
+/// and the final range is scanned linearly. If no message is found in the final range, the scan continues
+/// until the end of ``bytes``.
+///
+pub fn seek_time(bytes: &[u8], target_us: i64) -> usize {
+    let search = ContainedBySearch::<DltStorageEntry>::new();
+
+    let mut lo = resync(&search, bytes, 0);
+    let mut hi = bytes.len();
+    while hi - lo > LINEAR_SCAN_SIZE {
+        let pos = resync(&search, bytes, lo + (hi - lo) / 2);
+        if pos >= hi {
+            break;
+        }
+        match probe(bytes, pos) {
+            Some(time) if time < target_us => lo = pos,
+            _ => hi = pos,
+        }
+    }
+
+    dltit_offset(&bytes[lo..])
+        .find(|(_, e)| e.storage_header.timestamp_us() >= target_us)
+        .map(|(offset, _)| lo + offset)
+        .unwrap_or(bytes.len())
+}
+
+///
+/// ``time_range``: returns the range of ``bytes`` that starts with the first message at or after ``from_us`` and ends
+/// with the first message after ``to_us``, see ``seek_time``. If storage times are not monotonic, the messages of the
+/// range still need to be filtered.
+///
+pub fn time_range(bytes: &[u8], from_us: i64, to_us: i64) -> Range<usize> {
+    let start = seek_time(bytes, from_us);
+    let end = start + seek_time(&bytes[start..], to_us.saturating_add(1));
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dltbuffer::DltBuffer;
+
+    use super::*;
+
+    fn check(bytes: &[u8]) {
+        let entries: Vec<(usize, i64)> = dltit_offset(bytes).map(|(offset, e)| (offset, e.storage_header.timestamp_us())).collect();
+        for i in (0..entries.len()).step_by(entries.len() / 50) {
+            let (offset, time) = entries[i];
+            let found = seek_time(bytes, time);
+            assert!(found <= offset, "message {} at {} found at {}", i, offset, found);
+            let (_, e) = dltit_offset(&bytes[found..]).next().unwrap();
+            assert!(e.storage_header.timestamp_us() >= time);
+        }
+        assert_eq!(0, seek_time(bytes, i64::MIN));
+        assert_eq!(bytes.len(), seek_time(bytes, i64::MAX));
+    }
+
+    #[test]
+    fn seek_monotonic() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex002.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        check(bytes);
+
+        // the first message at or after the target is found exactly
+        let entries: Vec<(usize, i64)> = dltit_offset(bytes).map(|(offset, e)| (offset, e.storage_header.timestamp_us())).collect();
+        let (offset, time) = entries[5000];
+        let first = entries.iter().find(|(_, t)| *t >= time).unwrap().0;
+        assert_eq!(first, seek_time(bytes, time));
+        assert!(offset >= first);
+    }
+
+    #[test]
+    fn seek_mostly_monotonic() {
+        // lc_ex003.dlt contains a few messages with out-of-order storage times
+        let buf = DltBuffer::mmap_file("../test/lc_ex003.dlt").expect("test file");
+        check(buf.as_slice());
+    }
+
+    #[test]
+    fn seek_resync() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex004.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        let search = ContainedBySearch::<DltStorageEntry>::new();
+
+        let offsets: Vec<usize> = dltit_offset(bytes).map(|(offset, _)| offset).take(100).collect();
+        for w in offsets.windows(2) {
+            assert_eq!(w[0], resync(&search, bytes, w[0]));
+            assert_eq!(w[1], resync(&search, bytes, w[0] + 1));
+            assert_eq!(w[1], resync(&search, bytes, w[1] - 1));
+        }
+        assert_eq!(bytes.len(), resync(&search, bytes, bytes.len() - 1));
+    }
+
+    #[test]
+    fn seek_time_range() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex002.dlt").expect("test file");
+        let bytes = buf.as_slice();
+        let times: Vec<i64> = dltit(bytes).map(|e| e.storage_header.timestamp_us()).collect();
+        let (from, to) = (times[2000], times[9000]);
+
+        let range = time_range(bytes, from, to);
+        let found = dltit(&bytes[range]).filter(|e| (from..=to).contains(&e.storage_header.timestamp_us())).count();
+        assert_eq!(times.iter().filter(|t| (from..=to).contains(*t)).count(), found);
+    }
+}
@@ -0,0 +1,133 @@
+use std::io::{self, Write};
+
+use crate::{dlt_v1::{dltit, DltStorageEntry}, dltfilter::DltFilter, dltverbose::{arguments, DltValue}};
+
+/// Verbose argument of a time series, selected by position or by name.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum DltArgumentRef {
+    Index(usize),
+    Name(Vec<u8>),
+}
+
+/// Time of the points of a time series.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum DltSeriesTime {
+    /// Storage time in microseconds since 1.1.1970.
+    Storage,
+    /// ECU timestamp of the standard header in microseconds since ECU start. Messages without timestamp are skipped.
+    Ecu,
+}
+
+///
+/// ``DltSeries``: extracts a numeric time series from verbose messages.
+///
+/// A message contributes a point if it matches ``filter``, e.g., ``apid == "SYS" && ctid == "CPU"``, and its first
+/// argument is a string starting with ``prefix``, if given. The value is the ``argument`` converted to ``f64``, see
+/// ``DltValue::as_f64``; messages without such an argument are skipped.
+///
+pub struct DltSeries {
+    pub filter: DltFilter,
+    pub prefix: Option<Vec<u8>>,
+    pub argument: DltArgumentRef,
+    pub time: DltSeriesTime,
+}
+
+impl DltSeries {
+    pub fn new(filter: DltFilter, argument: DltArgumentRef, time: DltSeriesTime) -> Self {
+        Self { filter, prefix: None, argument, time }
+    }
+
+    /// Returns the point of ``entry`` as (time in microseconds, value).
+    pub fn point(&self, entry: &DltStorageEntry) -> Option<(i64, f64)> {
+        if !self.filter.matches(entry) {
+            return None
+        }
+        let mut args = arguments(entry)?.peekable();
+        if let Some(prefix) = &self.prefix {
+            match args.peek()?.value {
+                DltValue::Str(s) if s.starts_with(prefix) => (),
+                _ => return None,
+            }
+        }
+        let value = match &self.argument {
+            DltArgumentRef::Index(index) => args.nth(*index)?.value.as_f64()?,
+            DltArgumentRef::Name(name) => args.find(|arg| arg.name == Some(name.as_slice()))?.value.as_f64()?,
+        };
+        let time = match self.time {
+            DltSeriesTime::Storage => entry.storage_header.timestamp_us(),
+            DltSeriesTime::Ecu => entry.dlt.timestamp()? as i64 * 100,
+        };
+        Some((time, value))
+    }
+
+    /// Returns the points of the messages of ``bytes``, e.g., to be reduced with a ``matchit::generator`` pipeline.
+    pub fn points<'a>(&'a self, bytes: &'a [u8]) -> impl Iterator<Item = (i64, f64)> + 'a {
+        dltit(bytes).filter_map(|entry| self.point(&entry))
+    }
+}
+
+///
+/// ``write_csv``: writes ``points`` as CSV with the columns ``time`` (seconds) and ``value``.
+/// Returns the number of points.
+///
+pub fn write_csv<W: Write>(points: impl Iterator<Item = (i64, f64)>, writer: &mut W) -> Result<usize, io::Error> {
+    writeln!(writer, "time,value")?;
+    let mut num = 0;
+    for (time, value) in points {
+        writeln!(writer, "{}.{:06},{}", time.div_euclid(1_000_000), time.rem_euclid(1_000_000), value)?;
+        num += 1;
+    }
+    Ok(num)
+}
+
+#[cfg(test)]
+mod tests {
+    use matchit::generator::{generator::Generator, reducer::ReducerTrait};
+
+    use crate::{dltbuffer::DltBuffer, dlttest::{float32, string, TestMessage}};
+
+    use super::*;
+
+    fn message(secs: u32, apid: &[u8; 4], text: &str, load: f32) -> Vec<u8> {
+        TestMessage { secs, timestamp: Some(secs * 10), extended: Some((2, *apid, *b"CTX1")), ..Default::default() }
+            .bytes(&[string(text), float32("load", "%", load)].concat())
+    }
+
+    #[test]
+    fn series_points() {
+        let bytes = [
+            message(1, b"SYS\0", "CPU load", 10.0),
+            message(2, b"APP\0", "CPU load", 99.0),
+            message(3, b"SYS\0", "memory", 50.0),
+            message(4, b"SYS\0", "CPU load", 30.5),
+        ].concat();
+
+        let mut series = DltSeries::new(DltFilter::parse(r#"apid == "SYS""#).unwrap(), DltArgumentRef::Index(1), DltSeriesTime::Storage);
+        series.prefix = Some(b"CPU".to_vec());
+        let points: Vec<(i64, f64)> = series.points(&bytes).collect();
+        assert_eq!(vec![(1_000_000, 10.0), (4_000_000, 30.5)], points);
+
+        let series = DltSeries::new(DltFilter::True, DltArgumentRef::Name(b"load".to_vec()), DltSeriesTime::Ecu);
+        let points: Vec<(i64, f64)> = series.points(&bytes).collect();
+        assert_eq!(vec![(1000, 10.0), (2000, 99.0), (3000, 50.0), (4000, 30.5)], points);
+
+        // reduced with a generator
+        let mut max = Generator::fold(f64::MIN, |acc: f64, (_, value): (i64, f64)| acc.max(value));
+        series.points(&bytes).for_each(|point| max.next(point));
+        assert_eq!(99.0, max.finalize());
+
+        let mut csv = vec![];
+        assert_eq!(4, write_csv(series.points(&bytes), &mut csv).unwrap());
+        assert_eq!("time,value\n0.001000,10\n0.002000,99\n0.003000,50\n0.004000,30.5\n", String::from_utf8(csv).unwrap());
+    }
+
+    #[test]
+    fn series_trace() {
+        // all arguments of 68b_verbose.dlt are numeric except the raw data at index 4
+        let buf = DltBuffer::mmap_file("../test/68b_verbose.dlt").expect("test file");
+        let series = DltSeries::new(DltFilter::True, DltArgumentRef::Index(6), DltSeriesTime::Storage);
+        assert_eq!(10000, series.points(buf.as_slice()).count());
+        let series = DltSeries::new(DltFilter::True, DltArgumentRef::Index(4), DltSeriesTime::Storage);
+        assert_eq!(0, series.points(buf.as_slice()).count());
+    }
+}
@@ -0,0 +1,169 @@
+use std::{io::{self, Read}, mem};
+
+use matchit::{FromBytesReadableTrait, WithOffset};
+
+use crate::{dlt_v1::{DltStorageEntry, DltStorageHeader}, dltbuffer::complete_prefix};
+
+const DEFAULT_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+///
+/// ``DltStreamBuffer``: reads DLT messages from any ``io::Read`` source, e.g., stdin, pipes or sockets,
+/// with bounded memory.
+///
+/// Data is read in chunks into a sliding window. Each call of ``DltStreamBuffer::next_slice`` returns
+/// a slice that ends at a DLT message boundary together with its offset within the stream, so that
+/// slice-based iterators like ``dltit`` or ``DltGrepIterator`` can be used on it. A message spanning
+/// two chunks is kept in the window until it is complete.
+///
+pub struct DltStreamBuffer<R> {
+    reader: R,
+    window: Vec<u8>,
+    chunk_size: usize,
+    complete: usize,
+    position: usize,
+    eof: bool,
+}
+
+impl<R: Read> DltStreamBuffer<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_chunk_size(reader, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates a stream buffer reading chunks of ``chunk_size`` bytes. The chunk size is at least the size of
+    /// the largest possible DLT message.
+    pub fn with_chunk_size(reader: R, chunk_size: usize) -> Self {
+        let chunk_size = chunk_size.max(DltStorageEntry::max_len() + mem::size_of::<DltStorageHeader>() + 1);
+        Self {
+            reader,
+            window: Vec::with_capacity(2 * chunk_size),
+            chunk_size,
+            complete: 0,
+            position: 0,
+            eof: false,
+        }
+    }
+
+    /// Returns the offset and the next slice of complete DLT messages, or ``None`` at the end of the stream.
+    /// At the end of the stream, the returned slice contains all remaining data.
+    pub fn next_slice(&mut self) -> Result<Option<WithOffset<&[u8]>>, io::Error> {
+        self.consume(self.complete);
+        self.complete = 0;
+
+        loop {
+            self.fill()?;
+            if self.window.is_empty() {
+                return Ok(None);
+            }
+            if self.eof {
+                self.complete = self.window.len();
+                return Ok(Some((self.position, &self.window)));
+            }
+
+            let (end, skip) = complete_prefix(&self.window);
+            if end > 0 {
+                self.complete = end;
+                return Ok(Some((self.position, &self.window[..end])));
+            }
+            if let Some(skip) = skip {
+                // the data at the start of the window cannot become a valid message anymore
+                self.consume(skip);
+            }
+        }
+    }
+
+    fn fill(&mut self) -> Result<(), io::Error> {
+        while !self.eof && self.window.len() < self.chunk_size {
+            let len = self.window.len();
+            self.window.resize(self.chunk_size, 0);
+            match self.reader.read(&mut self.window[len..]) {
+                Ok(0) => {
+                    self.window.truncate(len);
+                    self.eof = true;
+                },
+                Ok(num) => self.window.truncate(len + num),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => self.window.truncate(len),
+                Err(e) => {
+                    self.window.truncate(len);
+                    return Err(e);
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn consume(&mut self, num: usize) {
+        self.window.drain(..num);
+        self.position += num;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use crate::{dlt_v1::{dltit, dltit_offset}, dltbuffer::DltBuffer, DltGrepIterator};
+
+    use super::*;
+
+    /// returns at most 1000 bytes per read call
+    struct SlowReader<R>(R);
+    impl<R: Read> Read for SlowReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(1000);
+            self.0.read(&mut buf[..len])
+        }
+    }
+
+    #[test]
+    fn stream_count() {
+        let file = File::open("../test/lc_ex004.dlt").expect("test file");
+        let mut stream = DltStreamBuffer::with_chunk_size(SlowReader(file), 0);
+
+        let mut count = 0;
+        let mut slices = 0;
+        while let Some((_, slice)) = stream.next_slice().expect("valid read") {
+            count += dltit(slice).count();
+            slices += 1;
+        }
+        assert_eq!(52451, count);
+        assert!(slices > 1);
+    }
+
+    #[test]
+    fn stream_offsets() {
+        let buf = DltBuffer::mmap_file("../test/lc_ex005.dlt").expect("test file");
+        let expected: Vec<usize> = dltit_offset(buf.as_slice()).map(|(offset, _)| offset).collect();
+
+        let mut stream = DltStreamBuffer::with_chunk_size(buf.as_slice(), 100000);
+        let mut offsets = vec![];
+        while let Some((position, slice)) = stream.next_slice().expect("valid read") {
+            offsets.extend(dltit_offset(slice).map(|(offset, _)| position + offset));
+        }
+        assert_eq!(expected, offsets);
+    }
+
+    #[test]
+    fn stream_grep() {
+        let file = File::open("../test/lc_ex003.dlt").expect("test file");
+        let mut stream = DltStreamBuffer::with_chunk_size(file, 0);
+
+        let mut count = 0;
+        while let Some((_, slice)) = stream.next_slice().expect("valid read") {
+            count += DltGrepIterator::new("Counter", slice, 0).count();
+        }
+        assert_eq!(1323, count);
+    }
+
+    #[test]
+    fn stream_chained() {
+        let f1 = File::open("../test/lc_ex002.dlt").expect("test file");
+        let f2 = File::open("../test/lc_ex006.dlt").expect("test file");
+        let mut stream = DltStreamBuffer::new(f1.chain(f2));
+
+        let mut count = 0;
+        while let Some((_, slice)) = stream.next_slice().expect("valid read") {
+            count += dltit(slice).count();
+        }
+        assert_eq!(11696 + 10001, count);
+    }
+}
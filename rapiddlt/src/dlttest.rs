@@ -0,0 +1,53 @@
+use zerocopy::AsBytes;
+
+use crate::dlt_v1::{DltExtendedHeader, DltHTyp, DltLogMessageTypeInfo, DltMessageType, DltStandardHeader, DltStorageHeader, MessageType};
+
+///
+/// TestMessage
+///
+/// Headers of a log message of level info for unit tests, ``bytes(payload)`` returns the message with its storage
+/// header. The default has no ECU ID and no timestamp in the standard header and an extended header with one argument.
+///
+pub struct TestMessage {
+    pub secs: u32,
+    pub ecu: [u8; 4],
+    /// Adds ``ecu`` to the standard header.
+    pub with_ecu_id: bool,
+    pub timestamp: Option<u32>,
+    pub msb_first: bool,
+    /// Extended header with number of arguments, APID and CTID.
+    pub extended: Option<(u8, [u8; 4], [u8; 4])>,
+}
+
+impl Default for TestMessage {
+    fn default() -> Self {
+        Self { secs: 1, ecu: *b"ECU1", with_ecu_id: false, timestamp: None, msb_first: false, extended: Some((1, *b"APP1", *b"CTX1")) }
+    }
+}
+
+impl TestMessage {
+    pub fn bytes(&self, payload: &[u8]) -> Vec<u8> {
+        let sh = DltStorageHeader::new([b'D',b'L',b'T', 0x1], self.secs, 0, self.ecu);
+        let ecu: &[u8] = if self.with_ecu_id { &self.ecu } else { &[] };
+        let timestamp = self.timestamp.map(|t| t.to_be_bytes().to_vec()).unwrap_or_default();
+        let ext = self.extended.map(|(noar, apid, ctid)| {
+            let msin = MessageType::create_message_type(true, DltMessageType::DltTypeLog(DltLogMessageTypeInfo::DltLogInfo));
+            DltExtendedHeader::new(msin, noar, apid, ctid).as_bytes().to_vec()
+        }).unwrap_or_default();
+        let length = (std::mem::size_of::<DltStandardHeader>() + ecu.len() + timestamp.len() + ext.len() + payload.len()) as u16;
+        let htyp = DltHTyp::new(self.extended.is_some(), self.msb_first, self.with_ecu_id, false, self.timestamp.is_some(), 1);
+        let header = DltStandardHeader::new(htyp, 0, length);
+        [sh.as_bytes(), header.as_bytes(), ecu, &timestamp, &ext, payload].concat()
+    }
+}
+
+/// Verbose string argument, little endian.
+pub fn string(s: &str) -> Vec<u8> {
+    [0x200u32.to_le_bytes().as_slice(), &(s.len() as u16 + 1).to_le_bytes(), s.as_bytes(), &[0]].concat()
+}
+
+/// Verbose 32 bit float argument with name and unit, little endian.
+pub fn float32(name: &str, unit: &str, value: f32) -> Vec<u8> {
+    [0x883u32.to_le_bytes().as_slice(), &(name.len() as u16 + 1).to_le_bytes(), &(unit.len() as u16 + 1).to_le_bytes(),
+        name.as_bytes(), &[0], unit.as_bytes(), &[0], &value.to_le_bytes()].concat()
+}
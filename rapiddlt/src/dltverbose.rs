@@ -0,0 +1,289 @@
+use std::ops::Range;
+
+use matchit::searchable::grepit::parse_f64;
+
+use crate::dlt_v1::DltStorageEntry;
+
+#[allow(dead_code)]
+enum DltTypeInfoMask {
+    TypeLength = 0xF,
+    Bool = 0x10,
+    Signed = 0x20,
+    Unsigned = 0x40,
+    Float = 0x80,
+    Array = 0x100,
+    String = 0x200,
+    Raw = 0x400,
+    VariableInfo = 0x800,
+    FixedPoint = 0x1000,
+    TraceInfo = 0x2000,
+    Struct = 0x4000,
+}
+
+/// Value of a verbose argument. Fixed point values are converted to ``Float``.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum DltValue<'bytes> {
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    /// String without the terminating zero.
+    Str(&'bytes [u8]),
+    Raw(&'bytes [u8]),
+}
+
+impl DltValue<'_> {
+    /// Numeric value, booleans are ``0`` or ``1``, strings are parsed.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            DltValue::Bool(v) => Some(*v as u8 as f64),
+            DltValue::Int(v) => Some(*v as f64),
+            DltValue::UInt(v) => Some(*v as f64),
+            DltValue::Float(v) => Some(*v),
+            DltValue::Str(v) => parse_f64(std::str::from_utf8(v).ok()?.trim()),
+            DltValue::Raw(_) => None,
+        }
+    }
+}
+
+/// Verbose argument with optional name and unit.
+#[derive(PartialEq, Clone, Debug)]
+pub struct DltArgument<'bytes> {
+    pub type_info: u32,
+    pub name: Option<&'bytes [u8]>,
+    pub unit: Option<&'bytes [u8]>,
+    pub value: DltValue<'bytes>,
+    /// Range of the value within the payload.
+    pub data: Range<usize>,
+}
+
+///
+/// ``DltArguments``: iterates over the arguments of a verbose message payload.
+///
+/// Supported are boolean, integer (up to 64 bit), float (32 and 64 bit), fixed point, string, trace info and raw
+/// arguments. Arrays, structs and truncated arguments end the iteration.
+///
+pub struct DltArguments<'bytes> {
+    payload: &'bytes [u8],
+    offset: usize,
+    remaining: u8,
+    msb_first: bool,
+}
+
+///
+/// ``arguments``: returns the arguments of ``entry``, or ``None`` if ``entry`` is not a verbose message.
+///
+pub fn arguments<'bytes>(entry: &DltStorageEntry<'bytes>) -> Option<DltArguments<'bytes>> {
+    let ext = entry.dlt.extended_header()?;
+    if !ext.msin.is_verbose() {
+        return None
+    }
+    Some(DltArguments {
+        payload: entry.dlt.payload()?,
+        offset: 0,
+        remaining: ext.noar,
+        msb_first: entry.dlt.header.header_type.is_msb_first(),
+    })
+}
+
+impl<'bytes> DltArguments<'bytes> {
+    fn take(&mut self, len: usize) -> Option<&'bytes [u8]> {
+        let bytes = self.payload.get(self.offset..self.offset.checked_add(len)?)?;
+        self.offset += len;
+        Some(bytes)
+    }
+
+    fn uint(&mut self, len: usize) -> Option<u64> {
+        let bytes = self.take(len)?;
+        let fold = |acc: u64, b: &u8| acc << 8 | *b as u64;
+        Some(if self.msb_first { bytes.iter().fold(0, fold) } else { bytes.iter().rev().fold(0, fold) })
+    }
+
+    fn int(&mut self, len: usize) -> Option<i64> {
+        let shift = 64 - 8 * len as u32;
+        Some(((self.uint(len)? << shift) as i64) >> shift)
+    }
+
+    /// Name of a string, trace info, raw or boolean argument.
+    fn name(&mut self, type_info: u32) -> Option<Option<&'bytes [u8]>> {
+        if type_info & DltTypeInfoMask::VariableInfo as u32 == 0 {
+            return Some(None)
+        }
+        let len = self.uint(2)? as usize;
+        Some(Some(trim_zero(self.take(len)?)))
+    }
+
+    fn read(&mut self) -> Option<DltArgument<'bytes>> {
+        let type_info = self.uint(4)? as u32;
+        let size = match type_info & DltTypeInfoMask::TypeLength as u32 {
+            1..=4 => 1usize << ((type_info & DltTypeInfoMask::TypeLength as u32) - 1),
+            _ => 0,
+        };
+        let has = |mask: DltTypeInfoMask| type_info & mask as u32 != 0;
+
+        if has(DltTypeInfoMask::Array) || has(DltTypeInfoMask::Struct) {
+            return None
+        }
+        if has(DltTypeInfoMask::String) || has(DltTypeInfoMask::TraceInfo) || has(DltTypeInfoMask::Raw) {
+            let len = self.uint(2)? as usize;
+            let name = self.name(type_info)?;
+            let start = self.offset;
+            let bytes = self.take(len)?;
+            let (value, data) = if has(DltTypeInfoMask::Raw) {
+                (DltValue::Raw(bytes), start..self.offset)
+            } else {
+                let s = trim_zero(bytes);
+                (DltValue::Str(s), start..start + s.len())
+            };
+            return Some(DltArgument { type_info, name, unit: None, value, data })
+        }
+
+        let (name, unit) = if has(DltTypeInfoMask::VariableInfo) && has(DltTypeInfoMask::Bool) {
+            (self.name(type_info)?, None)
+        } else if has(DltTypeInfoMask::VariableInfo) {
+            let name_len = self.uint(2)? as usize;
+            let unit_len = self.uint(2)? as usize;
+            (Some(trim_zero(self.take(name_len)?)), Some(trim_zero(self.take(unit_len)?)))
+        } else {
+            (None, None)
+        };
+        let fixed_point = if has(DltTypeInfoMask::FixedPoint) {
+            let quantization = f32::from_bits(self.uint(4)? as u32) as f64;
+            let offset = self.int(if size == 8 { 8 } else { 4 })? as f64;
+            Some((quantization, offset))
+        } else {
+            None
+        };
+
+        let start = self.offset;
+        let value = if has(DltTypeInfoMask::Bool) {
+            DltValue::Bool(self.uint(size.max(1))? != 0)
+        } else if has(DltTypeInfoMask::Signed) && size > 0 {
+            DltValue::Int(self.int(size)?)
+        } else if has(DltTypeInfoMask::Unsigned) && size > 0 {
+            DltValue::UInt(self.uint(size)?)
+        } else if has(DltTypeInfoMask::Float) && size == 4 {
+            DltValue::Float(f32::from_bits(self.uint(4)? as u32) as f64)
+        } else if has(DltTypeInfoMask::Float) && size == 8 {
+            DltValue::Float(f64::from_bits(self.uint(8)?))
+        } else {
+            return None
+        };
+        let value = match (fixed_point, value) {
+            (Some((quantization, offset)), DltValue::Int(v)) => DltValue::Float(v as f64 * quantization + offset),
+            (Some((quantization, offset)), DltValue::UInt(v)) => DltValue::Float(v as f64 * quantization + offset),
+            (_, value) => value,
+        };
+        Some(DltArgument { type_info, name, unit, value, data: start..self.offset })
+    }
+}
+
+impl<'bytes> Iterator for DltArguments<'bytes> {
+    type Item = DltArgument<'bytes>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None
+        }
+        match self.read() {
+            Some(arg) => {
+                self.remaining -= 1;
+                Some(arg)
+            },
+            None => {
+                self.remaining = 0;
+                None
+            },
+        }
+    }
+}
+
+fn trim_zero(bytes: &[u8]) -> &[u8] {
+    match bytes.iter().position(|b| *b == 0) {
+        Some(pos) => &bytes[..pos],
+        None => bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{dlt_v1::dltit, dltbuffer::DltBuffer, dlttest::TestMessage};
+    use matchit::FromBytesReadableTrait;
+
+    use super::*;
+
+    fn message(msb_first: bool, noar: u8, payload: &[u8]) -> Vec<u8> {
+        TestMessage { msb_first, extended: Some((noar, *b"APP1", *b"CTX1")), ..Default::default() }.bytes(payload)
+    }
+
+    #[test]
+    fn verbose_arguments() {
+        let mut payload = vec![];
+        payload.extend_from_slice(&0x200u32.to_le_bytes());
+        payload.extend_from_slice(&6u16.to_le_bytes());
+        payload.extend_from_slice(b"speed\0");
+        payload.extend_from_slice(&0x23u32.to_le_bytes());
+        payload.extend_from_slice(&(-42i32).to_le_bytes());
+        // unsigned 16 bit with name and unit
+        payload.extend_from_slice(&0x842u32.to_le_bytes());
+        payload.extend_from_slice(&5u16.to_le_bytes());
+        payload.extend_from_slice(&5u16.to_le_bytes());
+        payload.extend_from_slice(b"load\0km/h\0");
+        payload.extend_from_slice(&120u16.to_le_bytes());
+        payload.extend_from_slice(&0x84u32.to_le_bytes());
+        payload.extend_from_slice(&1.5f64.to_le_bytes());
+        payload.extend_from_slice(&0x11u32.to_le_bytes());
+        payload.push(1);
+        // fixed point 32 bit
+        payload.extend_from_slice(&0x1023u32.to_le_bytes());
+        payload.extend_from_slice(&0.5f32.to_le_bytes());
+        payload.extend_from_slice(&10i32.to_le_bytes());
+        payload.extend_from_slice(&4i32.to_le_bytes());
+        // array, not supported
+        payload.extend_from_slice(&0x123u32.to_le_bytes());
+
+        let bytes = message(false, 7, &payload);
+        let (_, entry) = DltStorageEntry::try_read(&bytes).unwrap();
+        let args: Vec<DltArgument> = arguments(&entry).unwrap().collect();
+        let values: Vec<DltValue> = args.iter().map(|a| a.value).collect();
+        assert_eq!(vec![DltValue::Str(b"speed"), DltValue::Int(-42), DltValue::UInt(120), DltValue::Float(1.5),
+            DltValue::Bool(true), DltValue::Float(12.0)], values);
+        assert_eq!(Some(b"load".as_slice()), args[2].name);
+        assert_eq!(Some(b"km/h".as_slice()), args[2].unit);
+        assert_eq!(b"speed", &entry.dlt.payload().unwrap()[args[0].data.clone()]);
+
+        let mut payload = vec![];
+        payload.extend_from_slice(&0x23u32.to_be_bytes());
+        payload.extend_from_slice(&(-7i32).to_be_bytes());
+        let bytes = message(true, 1, &payload);
+        let (_, entry) = DltStorageEntry::try_read(&bytes).unwrap();
+        assert_eq!(vec![DltValue::Int(-7)], arguments(&entry).unwrap().map(|a| a.value).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn verbose_as_f64() {
+        assert_eq!(Some(-1500.0), DltValue::Str(b" -1.5e3 ").as_f64());
+        assert_eq!(Some(1.0), DltValue::Bool(true).as_f64());
+        for word in [b"inf".as_slice(), b"NaN", b"infinity", b"-Infinity", b""] {
+            assert_eq!(None, DltValue::Str(word).as_f64());
+        }
+    }
+
+    #[test]
+    fn verbose_trace() {
+        // lc_ex003.dlt contains verbose messages with string arguments
+        let buf = DltBuffer::mmap_file("../test/lc_ex003.dlt").expect("test file");
+        let mut num = 0;
+        for entry in dltit(buf.as_slice()) {
+            if let Some(args) = arguments(&entry) {
+                let noar = entry.dlt.extended_header().unwrap().noar as usize;
+                let payload = entry.dlt.payload().unwrap();
+                let args: Vec<DltArgument> = args.collect();
+                assert_eq!(noar, args.len());
+                assert!(args.iter().all(|a| a.data.end <= payload.len()));
+                num += 1;
+            }
+        }
+        assert!(num > 0);
+    }
+}
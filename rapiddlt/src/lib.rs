@@ -1,9 +1,31 @@
 use dlt_v1::DltStorageEntry;
-use matchit::searchable::{grepit::GrepIterator, readfallbackit::ReadFallbackIterator};
+use matchit::searchable::{grepit::{GrepIterator, MultiGrepIterator, NamedCaptureIterator}, readfallbackit::ReadFallbackIterator};
 
+pub mod dltarchive;
 pub mod dltbuffer;
+pub mod dltcompress;
+pub mod dltcursor;
+pub mod dltfilter;
+pub mod dltfollow;
+pub mod dltgrep;
+pub mod dltidsearch;
+pub mod dltindex;
+pub mod dltlatency;
+pub mod dltmerge;
+pub mod dltmulti;
+pub mod dltreceiver;
+pub mod dltseek;
+pub mod dltseries;
+pub mod dltstream;
+#[cfg(test)]
+mod dlttest;
+pub mod dltverbose;
 pub mod dlt_v1;
 
 type DltIterator<'bytes,T> = ReadFallbackIterator<'bytes,T>;
 
-pub type DltGrepIterator<'bytes> = GrepIterator<'bytes, DltStorageEntry<'bytes>>;
\ No newline at end of file
+pub type DltGrepIterator<'bytes> = GrepIterator<'bytes, DltStorageEntry<'bytes>>;
+
+pub type DltMultiGrepIterator<'bytes> = MultiGrepIterator<'bytes, DltStorageEntry<'bytes>>;
+
+pub type DltCaptureIterator<'bytes> = NamedCaptureIterator<'bytes, DltStorageEntry<'bytes>>;
@@ -1,98 +1,81 @@
 
-use rapiddlt::{dlt_v1::{dltit, DltMessageType, DltStorageEntry}, dltbuffer::DltBuffer, DltGrepIterator};
-use matchit::{fromgenerator::FromAdaptFnCall, generator::generator::Generator, FromBytesReadableTrait };
+use rapiddlt::{dlt_v1::{dltit, dltit_offset, DltMessageType, DltStorageEntry}, dltarchive::DltArchive, dltbuffer::DltBuffer, dltcompress::{detect_decoder, open_decoder}, dltfilter::DltFilter, dltfollow::DltFollower, dltgrep::{grep_payload, DltContextIterator}, dltidsearch::id_search, dltindex::DltIndex, dltlatency::DltLatency, dltmerge::{dltmerge_to, DltMergeTime}, dltseek::time_range, dltseries::{write_csv, DltArgumentRef, DltSeries, DltSeriesTime}, dltstream::DltStreamBuffer, DltCaptureIterator, DltGrepIterator, DltMultiGrepIterator};
+use matchit::{fromgenerator::FromAdaptFnCall, parit::par_iter_ordered, generator::{approx::{DistinctCount, Quantiles, TopK}, generator::Generator, parallel::{par_apply, par_groupby}}, partition_from, searchable::grepit::{CaptureValue, MultiGrepPatterns}, FromBytesReadableTrait };
 use matchit::generator::adapter::AdapterTrait;
 
 const MIN_TIME_DMS: u32 = 18000000; // Timestamp since system start in 0.1 milliseconds
 
 enum ProcessingType {
-    Iterative,
     Count,
     CtrlMsg,
     CtrlMsgRaw,
     CtrlMsgGrepIt
 }
 
-fn multithreaded(mmap: DltBuffer, typ: ProcessingType) -> usize {
-    use rayon::prelude::*;
-
-    use std::thread::available_parallelism;
+fn threads() -> usize {
+    thread::available_parallelism().unwrap().get()
+}
 
-    let num: usize = available_parallelism().unwrap().get();
-    let slices = mmap.partition::<DltStorageEntry>(num);
+fn partitions(mmap: &[u8]) -> Vec<&[u8]> {
+    let num: usize = threads();
+    let slices = partition_from::<DltStorageEntry>(mmap, num);
     println!("available parallelism = {}, slices = {}", num, slices.len());
+    slices
+}
 
-    let result =
-    slices.into_par_iter()
-        .map(|slice| {
-            match typ {
-            ProcessingType::Iterative => lifecycle_iter(slice),
-            ProcessingType::Count => count(slice),
-            ProcessingType::CtrlMsg => count_hello_world(slice),
-            ProcessingType::CtrlMsgRaw => count_hello_world_raw(slice),
-            ProcessingType::CtrlMsgGrepIt => count_hello_world_grepit(slice),
-            }
-        });
-
-    result.sum()
+fn multithreaded(mmap: &[u8], typ: ProcessingType) -> usize {
+    par_apply(&partitions(mmap), threads(), |slice| {
+        match typ {
+        ProcessingType::Count => count(slice),
+        ProcessingType::CtrlMsg => count_hello_world(slice),
+        ProcessingType::CtrlMsgRaw => count_hello_world_raw(slice),
+        ProcessingType::CtrlMsgGrepIt => count_hello_world_grepit(slice),
+        }
+    }).unwrap_or_default()
 }
 
-fn par_continuous_timestamp_histogram(mmap: DltBuffer) -> BTreeMap<usize, usize>
+fn par_continuous_timestamp_histogram(slices: &[&[u8]]) -> BTreeMap<usize, usize>
 {
-    use rayon::prelude::*;
-
-    use std::thread::available_parallelism;
-
-    let num: usize = available_parallelism().unwrap().get();
-    let slices = mmap.partition::<DltStorageEntry>(num);
-    println!("available parallelism = {}, slices = {}", num, slices.len());
+    // groups of continuous timestamps spanning two partitions are stitched
+    let predicate = |a: &DltStorageEntry<'_>, b: &DltStorageEntry<'_>| b.storage_header.secs.get() >= a.storage_header.secs.get();
+    par_groupby(slices, threads(), dltit, predicate, || Generator::map(|r: &(DltStorageEntry, DltStorageEntry)| r.1.storage_header.secs.get() - r.0.storage_header.secs.get())
+        .split(|id| *id as usize, |_| Generator::count()))
+}
 
-    let result =
-    slices.into_par_iter()
-        .map(|slice| {
-            continuous_timestamp_histogram(slice)
-            }
-        );
+fn par_lifecycle_histogram(slices: &[&[u8]]) -> BTreeMap<usize, usize> {
+    let predicate = |a: &DltStorageEntry<'_>, b: &DltStorageEntry<'_>| a.dlt.ecu_id() == b.dlt.ecu_id() && b.dlt.timestamp() >= a.dlt.timestamp();
+    par_groupby(slices, threads(), |slice| dltit(slice).filter(|e| e.dlt.header.header_type.is_with_timestamp()), predicate,
+        || Generator::map(|r: &(DltStorageEntry, DltStorageEntry)| r.1.dlt.timestamp().unwrap() - r.0.dlt.timestamp().unwrap())
+            .split(|id| *id as usize / 10000, |_| Generator::count()))
+}
 
-    result.reduce(|| BTreeMap::new(), |mut acc,next| {
-        for (k,v) in next {
-            let e = acc.entry(k).or_insert(0);
-            *e += v; 
-        }
-        acc
-    })
+fn par_lifecycle_iter(slices: &[&[u8]]) -> usize {
+    let predicate = |a: &DltStorageEntry<'_>, b: &DltStorageEntry<'_>| b.dlt.timestamp() >= a.dlt.timestamp();
+    let r = par_groupby(slices, threads(), |slice| dltit(slice).filter(|e| e.dlt.header.header_type.is_with_timestamp()), predicate,
+        || Generator::filter(|r: &(DltStorageEntry, DltStorageEntry)| r.1.dlt.timestamp().unwrap() - r.0.dlt.timestamp().unwrap() >= MIN_TIME_DMS).count());
+    println!("{:?} lifecycles >= {}s", r, MIN_TIME_DMS / 10000);
+    r
 }
 
-fn par_timestamp_splitit(mmap: DltBuffer) -> BTreeMap<[u8; 4], (BTreeMap<u32, usize>, usize)>
+fn par_timestamp_splitit(mmap: &[u8]) -> BTreeMap<[u8; 4], (BTreeMap<u32, usize>, usize)>
 {
-    use rayon::prelude::*;
-
-    use std::thread::available_parallelism;
-
-    let num: usize = available_parallelism().unwrap().get();
-    let slices = mmap.partition::<DltStorageEntry>(num);
-    println!("available parallelism = {}, slices = {}", num, slices.len());
+    par_apply(&partitions(mmap), threads(), timestamp_splitit).unwrap_or_default()
+}
 
-    let result =
-    slices.into_par_iter()
-        .map(|slice| {
-            timestamp_splitit(slice)
-            }
-        );
+type ApproxStatistics<'a> = (Quantiles, (DistinctCount<&'a [u8]>, TopK<([u8; 4], [u8; 4])>));
 
-    result.reduce(|| BTreeMap::new(), |mut acc,next| {
-        for (k,v) in next {
-            let e = acc.entry(k).or_default();
-            for (k1,v1) in v.0 {
-                let e1 = (*e).0.entry(k1).or_default();
-                *e1 += v1;
-            }
-            (*e).1 += v.1;
-        }
-        acc
-    })
+fn approx_statistics(mmap: &[u8]) -> ApproxStatistics<'_> {
+    let sizes = Generator::map(|dlt: &DltStorageEntry| dlt.len() as f64).quantiles(200);
+    let payloads = Generator::map(|dlt: &DltStorageEntry| dlt.dlt.payload().unwrap_or(&[])).distinct_count(14);
+    let ids = Generator::filter(|dlt: &DltStorageEntry| dlt.dlt.extended_header().is_some())
+        .map(|dlt: &DltStorageEntry| dlt.dlt.extended_header().map(|ext| (ext.apid, ext.ctid)).unwrap_or_default())
+        .top_k(20);
+    dltit(mmap).reduce_with(Generator::fork(sizes, Generator::fork(payloads, ids)))
 }
 
+fn par_approx_statistics(mmap: &[u8]) -> ApproxStatistics<'_> {
+    par_apply(&partitions(mmap), threads(), approx_statistics).unwrap_or_else(|| approx_statistics(&[]))
+}
 
 fn count(mmap: &[u8]) -> usize {
     let it = dltit(mmap);
@@ -123,7 +106,22 @@ fn count_hello_world_grepit(mmap: &[u8]) -> usize {
     it.count()
 }
 
-// fn lifecycle_histogram_sorted(mmap: DltBuffer) -> BTreeMap<usize, usize> {
+/// Number of messages matching any pattern and number of messages matching each pattern.
+fn par_multigrep(mmap: &[u8], patterns: &MultiGrepPatterns) -> (usize, BTreeMap<usize, usize>) {
+    par_apply(&partition_from::<DltStorageEntry>(mmap, threads()), threads(), |slice| {
+        let mut counts = BTreeMap::new();
+        let num = DltMultiGrepIterator::new(patterns, slice, 0)
+            .inspect(|(_, (_, ids))| ids.iter().for_each(|id| *counts.entry(*id).or_insert(0) += 1))
+            .count();
+        (num, counts)
+    }).unwrap_or_default()
+}
+
+fn count_hello_world_grepit_payload(mmap: &[u8]) -> usize {
+    grep_payload("H.* World", mmap).count()
+}
+
+// fn lifecycle_histogram_sorted(mmap: &[u8]) -> BTreeMap<usize, usize> {
 //     let it = dltit(mmap.as_slice());
 //     let predicate = |a: &DltStorageEntry<'_>, b: &DltStorageEntry<'_>| b.dlt.timestamp() >= a.dlt.timestamp();
 
@@ -149,16 +147,16 @@ fn continuous_timestamp_histogram(mmap: &[u8]) -> BTreeMap<usize, usize> {
     result.split(|id| *id as usize , |_| Generator::count() )
 }
 
-fn mstp_info_histogram(mmap: DltBuffer) -> BTreeMap<(bool, Option<DltMessageType>), usize> {
-    let it = dltit(mmap.as_slice());
+fn mstp_info_histogram(mmap: &[u8]) -> BTreeMap<(bool, Option<DltMessageType>), usize> {
+    let it = dltit(mmap);
 
     it
         .filter(|dlt| dlt.dlt.header.header_type.is_extended_header())
         .split(|dlt|  dlt.dlt.extended_header().unwrap().msin.info(), |_| Generator::count() )
 }
 
-fn lifecycle_splitit(mmap: DltBuffer) -> BTreeMap<[u8;4],  (BTreeMap<u32, usize>, usize)> {
-    let it = dltit(mmap.as_slice());
+fn lifecycle_splitit(mmap: &[u8]) -> BTreeMap<[u8;4],  (BTreeMap<u32, usize>, usize)> {
+    let it = dltit(mmap);
 
     let predicate = |a: &DltStorageEntry<'_>, b: &DltStorageEntry<'_>| b.dlt.timestamp() >= a.dlt.timestamp();
     let mapping = |r: &(DltStorageEntry<'_>,DltStorageEntry<'_>)| (r.1.dlt.timestamp().unwrap() - r.0.dlt.timestamp().unwrap()) / 10000;
@@ -222,8 +220,8 @@ fn timestamp_splitit(mmap: &[u8]) -> BTreeMap<[u8; 4], (BTreeMap<u32, usize>, us
     result
 }
 
-fn lifecycle_histogram(mmap: DltBuffer) -> BTreeMap<usize, usize> {
-    let it = dltit(mmap.as_slice());
+fn lifecycle_histogram(mmap: &[u8]) -> BTreeMap<usize, usize> {
+    let it = dltit(mmap);
     let predicate = |a: &DltStorageEntry<'_>, b: &DltStorageEntry<'_>| a.dlt.ecu_id() == b.dlt.ecu_id() && b.dlt.timestamp() >= a.dlt.timestamp();
 
     let result = it
@@ -234,8 +232,8 @@ fn lifecycle_histogram(mmap: DltBuffer) -> BTreeMap<usize, usize> {
     result.split(|id| *id as usize / 10000, |_| Generator::count() )
 }
 
-fn histogram_payload(mmap: DltBuffer) -> BTreeMap<usize, usize> {
-    let it = dltit(mmap.as_slice());
+fn histogram_payload(mmap: &[u8]) -> BTreeMap<usize, usize> {
+    let it = dltit(mmap);
 
     let result = it
         .map(|dlt| dlt.dlt.payload().unwrap_or(&[0u8;0]).len())
@@ -243,8 +241,8 @@ fn histogram_payload(mmap: DltBuffer) -> BTreeMap<usize, usize> {
     result.split(|id| *id, |_| Generator::count() )
 }
 
-fn histogram_message(mmap: DltBuffer) -> BTreeMap<usize, usize> {
-    let it = dltit(mmap.as_slice());
+fn histogram_message(mmap: &[u8]) -> BTreeMap<usize, usize> {
+    let it = dltit(mmap);
 
     let result = it
         .map(|dlt| dlt.len())
@@ -254,8 +252,8 @@ fn histogram_message(mmap: DltBuffer) -> BTreeMap<usize, usize> {
 
 
 const IDX_BUCKET_SIZE:usize = 100000000;
-fn histogram_hello_world(mmap: DltBuffer) -> BTreeMap<usize, usize> {
-    let it = DltGrepIterator::new("H.* World", mmap.as_slice(), 0);
+fn histogram_hello_world(mmap: &[u8]) -> BTreeMap<usize, usize> {
+    let it = DltGrepIterator::new("H.* World", mmap, 0);
     it.map(|(offset, _)| offset)
         .split(|offset: &usize| offset / IDX_BUCKET_SIZE , |_| Generator::count())
 }
@@ -291,13 +289,124 @@ fn lifecycle_itertools(mmap: &[u8]) -> usize {
     r
 }
 
-use std::{env, collections::{BTreeMap}};
+fn follow_count(fname: &str) {
+    let mut follower = DltFollower::open(fname).expect("file not found");
+    let mut count = 0;
+    loop {
+        if follower.poll().expect("failed to read file") == 0 {
+            thread::sleep(Duration::from_millis(100));
+            continue;
+        }
+        count += dltit(follower.as_slice()).count();
+        println!("{:?} messages", count);
+    }
+}
+
+fn stream(fname: &str, test_name: &str) -> usize {
+    let reader: Box<dyn Read> = match fname {
+        "-" => detect_decoder(io::stdin().lock()).expect("failed to read input"),
+        _ => open_decoder(fname).expect("file not found"),
+    };
+    let f: fn(&[u8]) -> usize = match test_name {
+        "count" => count,
+        "count_hello_world" => count_hello_world,
+        "count_hello_world_grepit" => count_hello_world_grepit,
+        _ => panic!("wrong parameter")
+    };
+
+    let mut stream = DltStreamBuffer::new(reader);
+    let mut result = 0;
+    while let Some((_, slice)) = stream.next_slice().expect("failed to read input") {
+        result += f(slice);
+    }
+    result
+}
+
+use std::{env, collections::{BTreeMap}, fs::File, io::{self, Read, Write}, thread, time::Duration};
+
+#[derive(Default)]
+struct Options {
+    from: Option<i64>,
+    to: Option<i64>,
+    filter: Option<DltFilter>,
+    before: usize,
+    after: usize,
+}
+
+impl Options {
+    /// Combines ``filter`` with previously given filters.
+    fn add_filter(&mut self, filter: DltFilter) {
+        self.filter = Some(match self.filter.take() {
+            Some(previous) => DltFilter::And(vec![previous, filter]),
+            None => filter,
+        });
+    }
+}
+
+/// Removes the options from ``args``.
+fn parse_options(args: Vec<String>) -> (Vec<String>, Options) {
+    let mut options = Options::default();
+    let mut positional = vec![];
+    let mut it = args.into_iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--from" => options.from = Some(parse_time(&it.next().expect("--from needs a time"))),
+            "--to" => options.to = Some(parse_time(&it.next().expect("--to needs a time"))),
+            "--filter" => options.add_filter(DltFilter::parse(&it.next().expect("--filter needs an expression")).unwrap_or_else(|e| panic!("{}", e))),
+            "-A" => options.after = parse_count(it.next(), "-A"),
+            "-B" => options.before = parse_count(it.next(), "-B"),
+            "-C" => {
+                options.before = parse_count(it.next(), "-C");
+                options.after = options.before;
+            },
+            "--dlf" => options.add_filter(DltFilter::open_dlf(&it.next().expect("--dlf needs a file")).unwrap_or_else(|e| panic!("{}", e))),
+            _ => positional.push(arg),
+        }
+    }
+    (positional, options)
+}
+
+fn parse_count(arg: Option<String>, option: &str) -> usize {
+    arg.and_then(|n| n.parse().ok()).unwrap_or_else(|| panic!("{} needs a number of messages", option))
+}
+
+/// Parses seconds since 1.1.1970 with optional fraction, e.g., ``1699355353.65``, into microseconds.
+fn parse_time(time: &str) -> i64 {
+    let (secs, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let secs: i64 = secs.parse().expect("time needs to be seconds since 1.1.1970");
+    let usecs: i64 = format!("{:0<6}", &fraction[..fraction.len().min(6)]).parse().expect("invalid fraction of seconds");
+    secs * 1_000_000 + usecs
+}
+
+/// Restricts ``bytes`` to the messages stored between ``--from`` and ``--to``.
+fn time_window<'a>(bytes: &'a [u8], options: &Options) -> &'a [u8] {
+    if options.from.is_none() && options.to.is_none() {
+        return bytes
+    }
+    let range = time_range(bytes, options.from.unwrap_or(i64::MIN), options.to.unwrap_or(i64::MAX));
+    &bytes[range]
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let (args, options) = parse_options(env::args().collect());
     if args.len() < 4 {
-        println!("usage: {} <file_access_method> <test_name> <filename.dlt>", args[0]);
+        println!("usage: {} [--from <secs>] [--to <secs>] [--filter <expr>] [--dlf <filter.dlf>] <file_access_method> <test_name> <filename.dlt>", args[0]);
         println!("  runs ripdlt tests with different implementations");
+        println!("  files compressed with gzip, zstd, xz or bzip2 are decompressed with file_access_method 'mmap', 'read' and 'stream' (also on stdin), 'follow' and 'index' need uncompressed files");
+        println!("  file_access_method 'follow' watches a growing file, supported test_name: count");
+        println!("  file_access_method 'stream' reads the file (or stdin for '-') in chunks, supported test_name: count, count_hello_world, count_hello_world_grepit");
+        println!("  file_access_method 'archive' runs the test for each member of a zip or tar archive, an optional glob selects the members (default: *.dlt)");
+        println!("  file_access_method 'index' builds or uses the sidecar index <filename.dlt>.idx, supported test_name: build [stride], count");
+        println!("  --from and --to restrict the test to messages with storage time in the given range, in seconds since 1.1.1970");
+        println!("  --filter selects messages, e.g., 'apid in [\"NAV\",\"HMI\"] && level <= warn && payload =~ /timeout/', supported test_name: count, par_count, print, par_print");
+        println!("  a filter requiring a single ECU, APID or CTID searches the ID in the raw bytes instead of reading all messages");
+        println!("  --dlf selects messages with the positive and negative filters of a dlt-viewer filter file, like --filter");
+        println!("  usage: {} merge <storage_time|ecu_time> <output.dlt> <input.dlt>... merges files ordered by time", args[0]);
+        println!("  usage: {} [-A <num>] [-B <num>] [-C <num>] grep <regex> <filename.dlt> prints matching messages (offset:) with <num> messages (offset-) after/before/around them", args[0]);
+        println!("  usage: {} extract <regex> <filename.dlt> prints the storage time and the named captures, e.g., (?P<speed>\\d+), of each matching message as CSV", args[0]);
+        println!("  usage: {} [--filter <expr>] series <index|name> <filename.dlt> [storage_time|ecu_time] prints a verbose argument of the selected messages as CSV time series", args[0]);
+        println!("  usage: {} latency <key-regex> <filename.dlt> <start-filter> <end-filter> [storage_time|ecu_time] [timeout_ms] prints the latency between start and end messages with the same key, e.g., 'id=(?P<id>\\d+)', per key class (?P<class>...)", args[0]);
+        println!("  usage: {} multigrep <patterns.txt> <filename.dlt> counts the messages matching each regex of a file with one regex per line", args[0]);
 
         return;
     }
@@ -305,19 +414,221 @@ fn main() {
     let mmethod = &args[1];
     let fname = &args[3];
 
+    if mmethod == "follow" {
+        match args[2].as_str() {
+            "count" => follow_count(fname),
+            _ => panic!("wrong parameter")
+        }
+        return;
+    }
+    if mmethod == "stream" {
+        let r = stream(fname, &args[2]);
+        println!("{:?} messages", r);
+        return;
+    }
+
+    if mmethod == "merge" {
+        let time = match args[2].as_str() {
+            "storage_time" => DltMergeTime::Storage,
+            "ecu_time" => DltMergeTime::CorrectedEcu,
+            _ => panic!("wrong parameter")
+        };
+        let buffers: Vec<DltBuffer> = args[4..].iter().map(|f| DltBuffer::open(f).expect("file not found")).collect();
+        let slices: Vec<&[u8]> = buffers.iter().map(|b| b.as_slice()).collect();
+        let mut writer = io::BufWriter::new(File::create(fname).expect("cannot create output file"));
+        let r = dltmerge_to(&slices, time, &mut writer).and_then(|num| writer.flush().map(|_| num)).expect("cannot write output file");
+        println!("{:?} messages", r);
+        return;
+    }
+    if mmethod == "grep" {
+        let mmap = DltBuffer::open(fname).expect("file not found");
+        let bytes = time_window(mmap.as_slice(), &options);
+        let hits = DltGrepIterator::new(&args[2], bytes, 0);
+        for (i, group) in DltContextIterator::new(hits, bytes, options.before, options.after).enumerate() {
+            if i > 0 && options.before + options.after > 0 {
+                println!("--");
+            }
+            for (j, (offset, entry)) in group.messages.iter().enumerate() {
+                print_entry(*offset, entry, if group.matches.contains(&j) { ':' } else { '-' });
+            }
+        }
+        return;
+    }
+    if mmethod == "extract" {
+        let mmap = DltBuffer::open(fname).expect("file not found");
+        let it = DltCaptureIterator::new(&args[2], time_window(mmap.as_slice(), &options), 0);
+        println!("time,{}", it.names().join(","));
+        for (_, (entry, values)) in it {
+            let values: Vec<String> = values.iter().map(|value| match value {
+                Some(CaptureValue::Int(v)) => v.to_string(),
+                Some(CaptureValue::Float(v)) => v.to_string(),
+                Some(CaptureValue::Str(v)) => format!("\"{}\"", String::from_utf8_lossy(v).replace('"', "\"\"")),
+                None => String::new(),
+            }).collect();
+            println!("{}.{:06},{}", entry.storage_header.secs.get(), entry.storage_header.msecs.get(), values.join(","));
+        }
+        return;
+    }
+    if mmethod == "series" {
+        let argument = match args[2].parse() {
+            Ok(index) => DltArgumentRef::Index(index),
+            Err(_) => DltArgumentRef::Name(args[2].as_bytes().to_vec()),
+        };
+        let time = match args.get(4).map(|s| s.as_str()) {
+            None | Some("storage_time") => DltSeriesTime::Storage,
+            Some("ecu_time") => DltSeriesTime::Ecu,
+            _ => panic!("wrong parameter")
+        };
+        let series = DltSeries::new(options.filter.clone().unwrap_or(DltFilter::True), argument, time);
+        let mmap = DltBuffer::open(fname).expect("file not found");
+        let mut writer = io::BufWriter::new(io::stdout().lock());
+        write_csv(series.points(time_window(mmap.as_slice(), &options)), &mut writer).and_then(|_| writer.flush()).expect("cannot write");
+        return;
+    }
+    if mmethod == "latency" {
+        let start = DltFilter::parse(&args[4]).unwrap_or_else(|e| panic!("{}", e));
+        let end = DltFilter::parse(&args[5]).unwrap_or_else(|e| panic!("{}", e));
+        let time = match args.get(6).map(|s| s.as_str()) {
+            None | Some("storage_time") => DltSeriesTime::Storage,
+            Some("ecu_time") => DltSeriesTime::Ecu,
+            _ => panic!("wrong parameter")
+        };
+        let mut latency = DltLatency::new(start, end, &args[2], time).unwrap_or_else(|e| panic!("{}", e));
+        if let Some(timeout) = args.get(7) {
+            latency.timeout = timeout.parse::<i64>().expect("timeout needs to be a number") * 1000;
+        }
+        let mmap = DltBuffer::open(fname).expect("file not found");
+        println!("class,matched,p50_us,p90_us,p99_us,max_us,unmatched_starts,unmatched_ends");
+        for (class, result) in latency.analyze(time_window(mmap.as_slice(), &options)) {
+            let quantiles: Vec<String> = [0.5, 0.9, 0.99, 1.0].iter()
+                .map(|q| result.latencies.quantile(*q).map(|v| v.to_string()).unwrap_or_default())
+                .collect();
+            println!("\"{}\",{},{},{},{}", String::from_utf8_lossy(&class).replace('"', "\"\""), result.latencies.count(),
+                quantiles.join(","), result.unmatched_starts, result.unmatched_ends);
+        }
+        return;
+    }
+    if mmethod == "multigrep" {
+        let lines: Vec<String> = std::fs::read_to_string(&args[2]).expect("file not found")
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_string())
+            .collect();
+        let patterns = MultiGrepPatterns::new(&lines).unwrap_or_else(|e| panic!("{}", e));
+        let mmap = DltBuffer::open(fname).expect("file not found");
+        let (num, counts) = par_multigrep(time_window(mmap.as_slice(), &options), &patterns);
+        for (id, pattern) in lines.iter().enumerate() {
+            println!("{:?} messages: {}", counts.get(&id).copied().unwrap_or_default(), pattern);
+        }
+        println!("{:?} messages", num);
+        return;
+    }
+    if mmethod == "index" {
+        let num: usize = thread::available_parallelism().unwrap().get();
+        match args[2].as_str() {
+            "build" => {
+                let stride = args.get(4).map(|s| s.parse().expect("stride needs to be a number")).unwrap_or(1);
+                let index = DltIndex::build_file(fname, stride, num).expect("cannot build index");
+                println!("{:?} index records", index.records().len());
+            },
+            "count" => {
+                let index = DltIndex::open_or_build(fname, 1, num).expect("cannot build index");
+                println!("{:?} messages", index.header().num_messages.get());
+            },
+            _ => panic!("wrong parameter")
+        }
+        return;
+    }
+    if mmethod == "archive" {
+        let pattern = args.get(4).map(|s| s.as_str()).unwrap_or("*.dlt");
+        let mut archive = DltArchive::open(fname).expect("not a zip or tar archive");
+        archive.for_each_member(pattern, |member| {
+            println!("{}:", member.name);
+            run_options(&args[2], member.buffer.as_slice(), &options);
+        }).expect("valid archive");
+        return;
+    }
+
     let mmap: DltBuffer = match mmethod.as_str() {
-        "mmap" => DltBuffer::mmap_file(fname).expect("file not found"),
+        "mmap" => DltBuffer::open(fname).expect("file not found"),
         "read" => DltBuffer::read_file(fname).expect("file not found"),
         _ => panic!("wrong paramter")
     };
 
-    match args[2].as_str() {
+    run_options(&args[2], mmap.as_slice(), &options);
+}
+
+fn run_options(test_name: &str, mmap: &[u8], options: &Options) {
+    let mmap = time_window(mmap, options);
+    match &options.filter {
+        Some(filter) => run_filtered(test_name, mmap, filter),
+        None => run(test_name, mmap),
+    }
+}
+
+fn run_filtered(test_name: &str, mmap: &[u8], filter: &DltFilter) {
+    match test_name {
+        "count" => {
+            let r = candidates(mmap, filter).filter(|(_, e)| filter.matches(e)).count();
+            println!("{:?} messages", r);
+        },
+        "par_count" => {
+            let r = par_apply(&partition_from::<DltStorageEntry>(mmap, threads()), threads(),
+                |slice| candidates(slice, filter).filter(|(_, e)| filter.matches(e)).count()).unwrap_or_default();
+            println!("{:?} messages", r);
+        },
+        "print" => {
+            for (offset, entry) in candidates(mmap, filter).filter(|(_, e)| filter.matches(e)) {
+                print_entry(offset, &entry, ' ');
+            }
+        },
+        "par_print" => {
+            // messages are filtered in parallel and printed in file order
+            let num = threads();
+            let slices = partition_from::<DltStorageEntry>(mmap, num * 4);
+            par_iter_ordered(&slices, num, 16,
+                |base, slice| candidates(slice, filter).filter(|(_, e)| filter.matches(e)).map(move |(offset, e)| (base + offset, e)),
+                |it| it.for_each(|(offset, entry)| print_entry(offset, &entry, ' ')));
+        },
+        _ => panic!("wrong parameter")
+    }
+}
+
+/// Messages that may match ``filter``: if the filter requires an ID, the messages with this ID are searched
+/// without reading all messages.
+fn candidates<'bytes>(mmap: &'bytes [u8], filter: &DltFilter) -> Box<dyn Iterator<Item = (usize, DltStorageEntry<'bytes>)> + 'bytes> {
+    match filter.required_id() {
+        Some((field, id)) => Box::new(id_search(mmap, field, id)),
+        None => Box::new(dltit_offset(mmap)),
+    }
+}
+
+/// Prints offset, ``separator``, storage time, ECU, APID, CTID and the printable characters of the payload.
+fn print_entry(offset: usize, entry: &DltStorageEntry, separator: char) {
+    let id = |id: &[u8]| String::from_utf8_lossy(id).trim_end_matches('\0').to_string();
+    let ecu = entry.dlt.ecu_id().map(|id| id.to_be_bytes()).unwrap_or(entry.storage_header.ecu);
+    let (apid, ctid) = entry.dlt.extended_header().map(|ext| (id(&ext.apid), id(&ext.ctid))).unwrap_or_default();
+    let payload: String = entry.dlt.payload().unwrap_or_default().iter()
+        .map(|c| if c.is_ascii_graphic() || *c == b' ' { *c as char } else { ' ' })
+        .collect();
+    println!("{}{}{}.{:06} {} {} {} {}", offset, separator, entry.storage_header.secs.get(), entry.storage_header.msecs.get(),
+        id(&ecu), apid, ctid, payload.split_whitespace().collect::<Vec<_>>().join(" "));
+}
+
+fn run(test_name: &str, mmap: &[u8]) {
+    match test_name {
         "histogram_lifecycles" =>{
             println!("Distribution of lifecycle durations:");
             for (k,v) in lifecycle_histogram(mmap) {
                 println!("{:?}-{:?} secs: {:?}", k, k+1, v);
             }
         },
+        "par_histogram_lifecycles" =>{
+            println!("Distribution of lifecycle durations:");
+            for (k,v) in par_lifecycle_histogram(&partitions(mmap)) {
+                println!("{:?}-{:?} secs: {:?}", k, k+1, v);
+            }
+        },
         "split_lifecycles" =>{
             println!("Distribution of lifecycle durations:");
             for (k,v) in lifecycle_splitit(mmap) {
@@ -326,7 +637,7 @@ fn main() {
         },
         "split_timestamp" =>{
             println!("Durations of periods where DLT storage header timestamps are continuous:");
-            for (k,v) in timestamp_splitit(mmap.as_slice()) {
+            for (k,v) in timestamp_splitit(mmap) {
                 println!("{} #lifecycles: {:?}", String::from_utf8(k.to_vec()).unwrap(), v);
             }
         },
@@ -338,13 +649,13 @@ fn main() {
         },
         "histogram_timestamp" =>{
             println!("Durations of periods where DLT storage header timestamps are continuous:");
-            for (k,v) in continuous_timestamp_histogram(mmap.as_slice()) {
+            for (k,v) in continuous_timestamp_histogram(mmap) {
                 println!("{:?}-{:?} secs: {:?}", k, k+1, v);
             }
         },
         "par_histogram_timestamp" =>{
             println!("Durations of periods where DLT storage header timestamps are continuous:");
-            for (k,v) in par_continuous_timestamp_histogram(mmap) {
+            for (k,v) in par_continuous_timestamp_histogram(&partitions(mmap)) {
                 println!("{:?}-{:?} secs: {:?}", k, k+1, v);
             }
         },
@@ -376,8 +687,21 @@ fn main() {
             println!("DLT messages in total: {} kB", total_size/1024);
         },
 
+        "par_approx_statistics" =>{
+            let (sizes, (payloads, ids)) = par_approx_statistics(mmap);
+            println!("DLT message length quantiles:");
+            for q in [0.5, 0.9, 0.99, 0.999] {
+                println!("{}: {}b", q, sizes.quantile(q).unwrap_or_default());
+            }
+            println!("Distinct payloads: ~{}", payloads.estimate());
+            println!("Most frequent APID/CTID:");
+            for ((apid, ctid), count, error) in ids.top() {
+                println!("{}/{}: {} (+-{})", String::from_utf8_lossy(&apid), String::from_utf8_lossy(&ctid), count, error);
+            }
+        },
+
         "count" => {
-            let r = count(mmap.as_slice());
+            let r = count(mmap);
             println!("{:?} messages", r);
         }
         "par_count" => {
@@ -392,15 +716,19 @@ fn main() {
             }
         },
         "count_hello_world" => {
-            let r = count_hello_world(mmap.as_slice());
+            let r = count_hello_world(mmap);
             println!("{:?} hello world messages", r);
         }
         "count_hello_world_raw" => {
-            let r = count_hello_world_raw(mmap.as_slice());
+            let r = count_hello_world_raw(mmap);
             println!("{:?} raw hello world matches", r);
         }
         "count_hello_world_grepit" => {
-            let r = count_hello_world_grepit(mmap.as_slice());
+            let r = count_hello_world_grepit(mmap);
+            println!("{:?} hello world messages", r);
+        }
+        "count_hello_world_grepit_payload" => {
+            let r = count_hello_world_grepit_payload(mmap);
             println!("{:?} hello world messages", r);
         }
         "par_count_hello_world" => {
@@ -415,9 +743,9 @@ fn main() {
             let r = multithreaded(mmap, ProcessingType::CtrlMsgGrepIt);
             println!("{:?} hello world messages", r);
         }
-        "par_iter" => {multithreaded(mmap, ProcessingType::Iterative);},
-        "iter" => {lifecycle_iter(mmap.as_slice());},
-        "itertools" => {lifecycle_itertools(mmap.as_slice());},
+        "par_iter" => {par_lifecycle_iter(&partitions(mmap));},
+        "iter" => {lifecycle_iter(mmap);},
+        "itertools" => {lifecycle_itertools(mmap);},
         _ => panic!("wrong parameter")
     };
 }
@@ -425,6 +753,7 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use matchit::generator::parallel::align_partitions;
 
     #[test]
     fn test_lifecycle_iter() {
@@ -432,6 +761,33 @@ mod tests {
         assert_eq!(199, lifecycle_iter(mmap.as_slice()));
     }
 
+    #[test]
+    fn test_par_continuous_timestamp_histogram() {
+        let mmap: DltBuffer = DltBuffer::mmap_file("../test/lc_ex004.dlt").expect("see test/HowTo.md");
+        let expected = continuous_timestamp_histogram(mmap.as_slice());
+
+        let predicate = |a: &DltStorageEntry<'_>, b: &DltStorageEntry<'_>| b.storage_header.secs.get() >= a.storage_header.secs.get();
+        let slices = partition_from::<DltStorageEntry>(mmap.as_slice(), 8);
+        let aligned = align_partitions(mmap.as_slice(), &slices, dltit_offset, predicate);
+        assert_ne!(Some(expected.clone()), par_apply(&slices, threads(), continuous_timestamp_histogram));
+        assert_eq!(Some(expected.clone()), par_apply(&aligned, threads(), continuous_timestamp_histogram));
+        assert_eq!(expected, par_continuous_timestamp_histogram(&slices));
+        assert_eq!(count(mmap.as_slice()), par_apply(&slices, threads(), count).unwrap());
+    }
+
+    #[test]
+    fn test_par_lifecycles() {
+        for file in ["../test/lc_ex002.dlt", "../test/lc_ex004.dlt", "../test/lc_ex006.dlt"] {
+            let mmap: DltBuffer = DltBuffer::mmap_file(file).expect("see test/HowTo.md");
+            let expected = lifecycle_histogram(mmap.as_slice());
+            for num in [2, 8, 64] {
+                let slices = partition_from::<DltStorageEntry>(mmap.as_slice(), num);
+                assert_eq!(expected, par_lifecycle_histogram(&slices));
+                assert_eq!(lifecycle_iter(mmap.as_slice()), par_lifecycle_iter(&slices));
+            }
+        }
+    }
+
     #[test]
     fn test_substring() {
         let mmap: DltBuffer = DltBuffer::mmap_file("../test/lc_ex003.dlt").expect("see test/HowTo.md");